@@ -0,0 +1,96 @@
+//! Compara o ajuste do parabolóide escalar com o SIMD e com o escalar em
+//! precisão dupla.
+//!
+//! Roda com `cargo bench --bench shape_operator`. Para cada malha de
+//! `res/models` (e um toro gerado, bem maior), prepara as vizinhanças e as
+//! bases tangentes, e mede o tempo de cada método. A última coluna é o
+//! tempo do ajuste em `f64` dividido pelo do escalar em `f32`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use gauss::{
+    double,
+    geom::{self, FitWeighting},
+    halfedge::HalfEdgeMesh,
+    mesh::Mesh,
+    surfaces,
+};
+
+/// Tempo mínimo gasto medindo cada método.
+const BUDGET: Duration = Duration::from_millis(500);
+
+/// Roda `f` repetidamente por pelo menos [`BUDGET`] e devolve o tempo médio
+/// de cada execução.
+fn time<T>(mut f: impl FnMut() -> T) -> Duration {
+    // Uma execução para aquecer os caches.
+    black_box(f());
+
+    let start = Instant::now();
+    let mut runs = 0;
+
+    while start.elapsed() < BUDGET {
+        black_box(f());
+        runs += 1;
+    }
+
+    start.elapsed() / runs
+}
+
+fn bench(name: &str, mesh: &Mesh) {
+    let mesh = HalfEdgeMesh::new(mesh).expect("invalid mesh");
+
+    let nbhds = geom::compute_neighborhoods(&mesh);
+    let normals = geom::compute_avg_normals(&mesh);
+    let bases = geom::compute_tangent_basis(&mesh, &nbhds, &normals);
+    let bases_f64 = double::compute_tangent_basis(&mesh, &nbhds, &double::compute_avg_normals(&mesh));
+
+    for weighting in [FitWeighting::Uniform, FitWeighting::InverseDistance] {
+        let scalar = time(|| geom::compute_shape_operator_checked(&mesh, &nbhds, &bases, weighting));
+        let simd = time(|| geom::compute_shape_operator_simd(&mesh, &nbhds, &bases, weighting));
+        let f64 = time(|| double::compute_shape_operator(&mesh, &nbhds, &bases_f64, weighting));
+
+        println!(
+            "{:<20} {:>9} {:<16} {:>12.3} {:>12.3} {:>8.2}x {:>12.3} {:>8.2}x",
+            name,
+            mesh.n_vertices(),
+            format!("{:?}", weighting),
+            scalar.as_secs_f64() * 1e3,
+            simd.as_secs_f64() * 1e3,
+            scalar.as_secs_f64() / simd.as_secs_f64(),
+            f64.as_secs_f64() * 1e3,
+            f64.as_secs_f64() / scalar.as_secs_f64(),
+        );
+    }
+}
+
+fn main() {
+    println!(
+        "{:<20} {:>9} {:<16} {:>12} {:>12} {:>9} {:>12} {:>9}",
+        "mesh", "vertices", "weighting", "scalar (ms)", "simd (ms)", "speedup", "f64 (ms)", "f64 cost",
+    );
+
+    let models_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/res/models");
+    let mut paths = std::fs::read_dir(models_dir)
+        .expect("failed to list res/models")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "obj"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let models = gauss::load_obj(&path).expect("failed to load model");
+        let name = path.file_stem().unwrap().to_string_lossy();
+
+        for model in models {
+            match Mesh::try_from(&model.mesh) {
+                Ok(mesh) => bench(&name, &mesh),
+                Err(e) => eprintln!("skipping {} [{}]: {}", name, model.name, e),
+            }
+        }
+    }
+
+    bench("torus (generated)", &surfaces::torus(2.0, 0.5, 800).mesh);
+}
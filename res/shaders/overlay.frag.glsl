@@ -0,0 +1,16 @@
+#version 410 core
+
+in float out_value;
+
+out vec4 frag_color;
+
+uniform sampler1D _colormap;
+
+void main() {
+    // Valores negativos são texto, pintado de branco.
+    if (out_value < 0.0) {
+        frag_color = vec4(1.0);
+    } else {
+        frag_color = vec4(texture(_colormap, out_value).rgb, 1.0);
+    }
+}
@@ -0,0 +1,21 @@
+#version 410 core
+
+/*
+ * Draws 2D triangles given in window pixels (origin at the top left
+ * corner, y pointing down) on top of the scene.
+ */
+
+layout(location = 0) in vec2 in_position;
+layout(location = 1) in float in_value;
+
+out float out_value;
+
+uniform vec2 _screen_size;
+
+void main() {
+    vec2 ndc = 2.0 * in_position / _screen_size - 1.0;
+
+    gl_Position = vec4(ndc.x, -ndc.y, 0.0, 1.0);
+
+    out_value = in_value;
+}
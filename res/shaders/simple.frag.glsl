@@ -2,24 +2,25 @@
 
 in vec3 out_position;
 in vec3 out_normal;
-in float out_curvature;
+in float out_value;
 
 out vec4 frag_color;
 
-void main() {
-    const vec3 lo = vec3(0.0, 0.0, 1.0);
-    const vec3 hi = vec3(1.0, 0.0, 0.0);
-
-    vec3 wtf_color = mix(lo, hi, out_curvature);
-
-    //frag_color = vec4(out_normal, 1.0);
-    frag_color = vec4(wtf_color, 1.0);
+// 1 para pintar a normal média como cor, 0 para pintar a grandeza escalar.
+uniform int _show_normals;
 
-    //vec3 u = abs(dFdxFine(out_normal));
-    //vec3 v = abs(dFdyFine(out_normal));
+// Tabela de cores, e os valores que vão no começo e no fim dela.
+uniform sampler1D _colormap;
+uniform vec2 _range;
 
-    //float lu = 10000000 * length(u);
-    //float lv = 10000000 * length(v);
-
-    //frag_color = vec4(lu, lv, 1.0, 1.0);
+void main() {
+    if (_show_normals != 0) {
+        frag_color = vec4(0.5 * normalize(out_normal) + 0.5, 1.0);
+    } else {
+        // Valores fora do intervalo ficam com a cor do extremo mais
+        // próximo (a textura usa CLAMP_TO_EDGE).
+        float t = (out_value - _range.x) / (_range.y - _range.x);
+
+        frag_color = vec4(texture(_colormap, t).rgb, 1.0);
+    }
 }
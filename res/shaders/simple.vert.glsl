@@ -1,17 +1,17 @@
 #version 410 core
 
 /*
- * This shader is only used to render the fullscren quad itself.
- * It doesn't do any shading whatsoever.
+ * Transforms the mesh and passes the normal and the scalar quantity of
+ * each vertex on to the fragment shader.
  */
 
 layout(location = 0) in vec3 in_position;
 layout(location = 1) in vec3 in_normal;
-layout(location = 2) in float in_curvature;
+layout(location = 2) in float in_value;
 
 out vec3 out_position;
 out vec3 out_normal;
-out float out_curvature;
+out float out_value;
 
 uniform mat4 _camera_mtx;
 uniform mat4 _model_mtx;
@@ -22,6 +22,5 @@ void main() {
 
     out_position = gl_Position.xyz;
     out_normal = in_normal;
-
-    out_curvature = (clamp(in_curvature, -5, 5) / 5 + 1)/2;
+    out_value = in_value;
 }
@@ -0,0 +1,203 @@
+//! # Análise sem janela
+//!
+//! Implementação do subcomando `gauss analyze`: carrega cada malha,
+//! roda os cálculos de [`gauss::pipeline`] e imprime um resumo das curvaturas.
+//! Não depende de SDL nem de OpenGL, então pode rodar em máquinas sem
+//! display.
+
+use std::{f32::consts::PI, path::Path};
+
+use gauss::{
+    discrete,
+    geom::{GeomError, VertexStatus},
+    halfedge::HalfEdgeMesh,
+    mesh::Mesh,
+    pipeline::{PipelineOptions, VertexAttributes},
+    stats::{self, Summary},
+};
+use serde::Serialize;
+
+use crate::cli::{AnalyzeArgs, OutputFormat};
+
+/// Resumo das curvaturas de um modelo.
+#[derive(Serialize, Debug)]
+pub struct MeshReport {
+    /// Arquivo de onde o modelo foi carregado.
+    pub file: String,
+    /// Nome do modelo dentro do arquivo.
+    pub model: String,
+    pub vertices: usize,
+    pub faces: usize,
+    /// Vértices interiores cujas curvaturas não puderam ser calculadas: os
+    /// que o ajuste do parabolóide rejeitou e os que ficaram com valores não
+    /// finitos.
+    pub degenerate_vertices: usize,
+    /// Vértices que não aparecem em nenhuma face.
+    pub isolated_vertices: usize,
+    /// Vértices com menos de três vizinhos.
+    pub too_few_neighbours: usize,
+    /// Vértices em que o ajuste do parabolóide é singular.
+    pub singular_fits: usize,
+    /// Vértices no bordo da malha.
+    pub boundary_vertices: usize,
+    /// Número de laços do bordo (zero numa malha fechada).
+    pub boundary_loops: usize,
+    /// Característica de Euler da malha.
+    pub euler_characteristic: i64,
+    /// Área total da malha.
+    pub area: f32,
+    /// Integral da curvatura gaussiana sobre a malha.
+    pub total_curvature: f32,
+    /// Integral da curvatura geodésica sobre o bordo.
+    pub boundary_curvature: f32,
+    /// Pelo teorema de Gauss-Bonnet, `total_curvature + boundary_curvature`
+    /// deve se aproximar desse valor, $2 \pi \chi$.
+    pub gauss_bonnet: f32,
+    /// Curvatura gaussiana.
+    pub gaussian: Option<Summary>,
+    /// Curvatura média.
+    pub mean: Option<Summary>,
+}
+
+/// Roda a análise de todos os arquivos pedidos.
+///
+/// Erros de carregamento são impressos em `stderr` e não interrompem a
+/// análise dos outros arquivos. Retorna `false` se algum arquivo falhou.
+pub fn run(args: &AnalyzeArgs) -> bool {
+    let mut reports = Vec::new();
+    let mut ok = true;
+
+    let opts = args.pipeline.options();
+
+    for path in &args.meshes {
+        match analyze_file(path, &opts) {
+            Ok(mut file_reports) => reports.append(&mut file_reports),
+            Err(e) => {
+                eprintln!("failed to load model {}: {}", path.display(), e);
+                ok = false;
+            }
+        }
+    }
+
+    match args.format {
+        OutputFormat::Text => {
+            for report in &reports {
+                print_text(report);
+            }
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&reports)
+                .expect("failed to serialize report");
+
+            println!("{}", json);
+        }
+    }
+
+    ok
+}
+
+/// Analisa todos os modelos contidos em um arquivo `.obj`.
+///
+/// Modelos inválidos (ver [`gauss::mesh::Mesh::validate`]) são reportados em
+/// `stderr` e pulados.
+pub fn analyze_file(path: &Path, opts: &PipelineOptions) -> Result<Vec<MeshReport>, tobj::LoadError> {
+    let models = gauss::load_obj(path)?;
+
+    let reports = models
+        .iter()
+        .filter_map(|model| match analyze_mesh(path, &model.name, &model.mesh, opts) {
+            Ok(report) => Some(report),
+            Err(e) => {
+                eprintln!("skipping model {} [{}]: {}", path.display(), model.name, e);
+                None
+            }
+        })
+        .collect();
+
+    Ok(reports)
+}
+
+fn analyze_mesh(
+    path: &Path,
+    name: &str,
+    mesh: &tobj::Mesh,
+    opts: &PipelineOptions,
+) -> Result<MeshReport, GeomError> {
+    let mesh = HalfEdgeMesh::new(&Mesh::try_from(mesh)?)?;
+    let attrs = VertexAttributes::compute_on(&mesh, opts);
+    let loops = discrete::compute_boundary_loops(&mesh);
+
+    let ks = &attrs.gaussian;
+    let hs = &attrs.mean;
+
+    // No bordo, valores não finitos são esperados (ver `discrete`), e não
+    // indicam um problema na malha.
+    let degenerate_vertices = (0..attrs.n_vertices())
+        .filter(|&i| {
+            attrs.status[i].is_degenerate()
+                || (!attrs.boundary[i] && (!ks[i].is_finite() || !hs[i].is_finite()))
+        })
+        .count();
+
+    let count = |s| attrs.status.iter().filter(|&&status| status == s).count();
+
+    Ok(MeshReport {
+        file: path.display().to_string(),
+        model: name.to_string(),
+        vertices: mesh.n_vertices(),
+        faces: mesh.n_faces(),
+        degenerate_vertices,
+        isolated_vertices: count(VertexStatus::Isolated),
+        too_few_neighbours: count(VertexStatus::TooFewNeighbours),
+        singular_fits: count(VertexStatus::SingularFit),
+        boundary_vertices: attrs.n_boundary(),
+        boundary_loops: loops.len(),
+        euler_characteristic: mesh.euler_characteristic(),
+        area: attrs.areas.iter().sum(),
+        total_curvature: stats::total_curvature(ks, &attrs.areas),
+        boundary_curvature: loops.iter().map(|l| l.total_geodesic_curvature).sum(),
+        gauss_bonnet: 2.0 * PI * mesh.euler_characteristic() as f32,
+        gaussian: Summary::from_values(ks),
+        mean: Summary::from_values(hs),
+    })
+}
+
+fn print_text(report: &MeshReport) {
+    println!("{} [{}]", report.file, report.model);
+    println!("  vertices:            {}", report.vertices);
+    println!("  faces:               {}", report.faces);
+    println!("  degenerate vertices: {}", report.degenerate_vertices);
+    if report.degenerate_vertices > 0 {
+        println!("    isolated:           {}", report.isolated_vertices);
+        println!("    too few neighbours: {}", report.too_few_neighbours);
+        println!("    singular fit:       {}", report.singular_fits);
+    }
+    if report.boundary_vertices > 0 {
+        println!("  boundary vertices:   {}", report.boundary_vertices);
+        println!("  boundary loops:      {}", report.boundary_loops);
+    }
+    println!("  euler char.:         {}", report.euler_characteristic);
+    println!("  area:                {}", report.area);
+    println!("  total curvature:     {}", report.total_curvature);
+    if report.boundary_vertices > 0 {
+        println!("  boundary curvature:  {}", report.boundary_curvature);
+    }
+    println!("  2 pi chi:            {}", report.gauss_bonnet);
+    println!();
+    println!(
+        "  {:<2} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "", "min", "p05", "p25", "median", "p75", "p95", "max", "mean",
+    );
+
+    for (label, summary) in [("K", &report.gaussian), ("H", &report.mean)] {
+        match summary {
+            Some(s) => println!(
+                "  {:<2} {:>12.5e} {:>12.5e} {:>12.5e} {:>12.5e} {:>12.5e} {:>12.5e} {:>12.5e} {:>12.5e}",
+                label, s.min, s.p05, s.p25, s.median, s.p75, s.p95, s.max, s.mean,
+            ),
+            None => println!("  {:<2} (no finite values)", label),
+        }
+    }
+
+    println!();
+}
@@ -0,0 +1,171 @@
+//! # Câmera
+//!
+//! A câmera do visualizador: uma *arcball*, que gira em volta de um ponto
+//! (o alvo) a uma certa distância dele.
+//!
+//! Arrastar o mouse gira a cena como se ela estivesse presa a uma esfera
+//! sob o cursor (o método de Ken Shoemake, "ARCBALL: A User Interface for
+//! Specifying Three-Dimensional Orientation Using a Mouse", 1992): os dois
+//! pontos da janela, no começo e no fim do movimento, são levados para a
+//! esfera, e a rotação é a que leva um no outro. Fora da esfera, usamos a
+//! hipérbole de Bell, para que a rotação não dê saltos na borda.
+//!
+//! Todas as posições na janela são em pixels da área de desenho do OpenGL,
+//! com a origem no canto superior esquerdo e o eixo y para baixo, como nos
+//! eventos do SDL. Em telas de alta densidade, esses pixels não são os
+//! mesmos das coordenadas do mouse, e quem chama deve convertê-las.
+
+use std::f32::consts::PI;
+
+use ultraviolet::{Mat4, Rotor3, Vec3};
+
+/// Campo de visão vertical.
+const FOV: f32 = PI / 3.0;
+
+/// Quanto cada passo da roda do mouse aproxima a câmera.
+const ZOOM_STEP: f32 = 0.9;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ArcballCamera {
+    /// Ponto em volta do qual a câmera gira.
+    target: Vec3,
+
+    /// Distância da câmera ao alvo.
+    distance: f32,
+
+    /// Orientação da cena vista pela câmera.
+    rotation: Rotor3,
+
+    /// Raio da esfera que contém a malha, usado para os planos de corte e
+    /// para limitar o zoom.
+    radius: f32,
+
+    /// Tamanho da área de desenho, em pixels.
+    size: (u32, u32),
+}
+
+impl ArcballCamera {
+    /// Uma câmera que enquadra a esfera de centro `center` e raio `radius`
+    /// numa janela de tamanho `size`.
+    pub fn new(center: Vec3, radius: f32, size: (u32, u32)) -> ArcballCamera {
+        let mut camera = ArcballCamera {
+            target: center,
+            distance: 1.0,
+            rotation: Rotor3::identity(),
+            radius: 1.0,
+            size,
+        };
+
+        camera.frame(center, radius);
+
+        camera
+    }
+
+    /// Enquadra a esfera de centro `center` e raio `radius`: aponta a câmera
+    /// para o centro, à distância em que a esfera cabe na janela, olhando
+    /// de um ângulo oblíquo.
+    pub fn frame(&mut self, center: Vec3, radius: f32) {
+        let radius = if radius > 0.0 && radius.is_finite() { radius } else { 1.0 };
+
+        // A esfera cabe no campo de visão vertical, ou no horizontal se a
+        // janela for mais alta que larga, com uma pequena folga.
+        let half_fov = 0.5 * FOV.min(self.horizontal_fov());
+
+        self.target = center;
+        self.radius = radius;
+        self.distance = 1.1 * radius / half_fov.sin();
+
+        // Parecido com a câmera antiga, que ficava em (2, 2, 2): um pouco
+        // de cima e de lado.
+        self.rotation = Rotor3::from_rotation_yz(PI / 6.0) * Rotor3::from_rotation_xz(PI / 4.0);
+    }
+
+    /// Troca o tamanho da janela.
+    pub fn set_size(&mut self, size: (u32, u32)) {
+        self.size = size;
+    }
+
+    /// Gira a cena pelo movimento do mouse de `from` para `to`.
+    pub fn rotate(&mut self, from: (i32, i32), to: (i32, i32)) {
+        let a = self.arcball_point(from);
+        let b = self.arcball_point(to);
+
+        if (a - b).mag_sq() < 1e-12 {
+            return;
+        }
+
+        self.rotation = (Rotor3::from_rotation_between(a, b) * self.rotation).normalized();
+    }
+
+    /// Aproxima (`steps` positivo) ou afasta (negativo) a câmera do alvo.
+    pub fn zoom(&mut self, steps: f32) {
+        self.distance = (self.distance * ZOOM_STEP.powf(steps))
+            .clamp(0.01 * self.radius, 100.0 * self.radius);
+    }
+
+    /// Move o alvo no plano da tela, de modo que o ponto sob o cursor
+    /// acompanhe um movimento de `(dx, dy)` pixels.
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        // Tamanho de um pixel no plano do alvo.
+        let pixel = 2.0 * self.distance * (0.5 * FOV).tan() / self.size.1.max(1) as f32;
+
+        // Os eixos da tela, escritos no espaço do mundo.
+        let inverse = self.rotation.reversed();
+        let mut right = Vec3::unit_x();
+        let mut up = Vec3::unit_y();
+        inverse.rotate_vec(&mut right);
+        inverse.rotate_vec(&mut up);
+
+        self.target -= (right * dx as f32 - up * dy as f32) * pixel;
+    }
+
+    /// Matriz da transformação do espaço do mundo para o da câmera.
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::from_translation(Vec3::new(0.0, 0.0, -self.distance))
+            * self.rotation.into_matrix().into_homogeneous()
+            * Mat4::from_translation(-self.target)
+    }
+
+    /// Matriz da projeção perspectiva.
+    pub fn projection_matrix(&self) -> Mat4 {
+        // Os planos de corte acompanham a distância, para que a precisão do
+        // depth buffer não dependa da escala da malha.
+        let near = (self.distance - 2.0 * self.radius).max(0.01 * self.distance);
+        let far = self.distance + 2.0 * self.radius;
+
+        ultraviolet::projection::perspective_gl(FOV, self.aspect(), near, far)
+    }
+
+    /// Matriz da transformação do espaço do mundo para o da tela.
+    pub fn matrix(&self) -> Mat4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+
+    fn aspect(&self) -> f32 {
+        self.size.0.max(1) as f32 / self.size.1.max(1) as f32
+    }
+
+    fn horizontal_fov(&self) -> f32 {
+        2.0 * ((0.5 * FOV).tan() * self.aspect()).atan()
+    }
+
+    /// Leva um ponto da janela para a esfera (ou hipérbole) da arcball, no
+    /// espaço da câmera. A esfera tem raio 1, com o centro no centro da
+    /// janela, e o menor lado da janela como diâmetro.
+    fn arcball_point(&self, (x, y): (i32, i32)) -> Vec3 {
+        let (w, h) = (self.size.0.max(1) as f32, self.size.1.max(1) as f32);
+        let scale = 0.5 * w.min(h);
+
+        let px = (x as f32 - 0.5 * w) / scale;
+        let py = (0.5 * h - y as f32) / scale;
+        let d2 = px * px + py * py;
+
+        let pz = if d2 <= 0.5 {
+            (1.0 - d2).sqrt()
+        } else {
+            0.5 / d2.sqrt()
+        };
+
+        Vec3::new(px, py, pz).normalized()
+    }
+}
@@ -0,0 +1,525 @@
+//! # Linha de comando
+//!
+//! Descrição dos argumentos aceitos pelo binário `gauss`.
+//!
+//! Sem subcomando, o binário abre o visualizador (equivalente a
+//! `gauss view`). O subcomando `gauss analyze` faz os cálculos sem abrir
+//! nenhuma janela, e o `gauss convergence` mede o erro dos estimadores em
+//! superfícies conhecidas.
+
+use std::path::PathBuf;
+
+use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+#[cfg(feature = "viewer")]
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use gauss::{
+    convergence::TestSurface,
+    geom::{FitSolver, FitWeighting, Neighborhood, NormalWeighting, Precision},
+    pipeline::{BoundaryHandling, GaussianEstimator, MeanEstimator, NormalSource, PipelineOptions},
+};
+#[cfg(feature = "viewer")]
+use gauss::{colormap::Colormap, viewer::{Quantity, RangeMode, ViewerOptions}};
+
+/// gauss: calculando geometria intrínseca de objetos 3D
+#[derive(Parser, Debug)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[cfg(feature = "viewer")]
+    #[command(flatten)]
+    pub view: ViewArgs,
+}
+
+impl Cli {
+    /// Lê os argumentos da linha de comando, como [`Parser::parse`], e
+    /// também recusa as combinações de opções que o `clap` não consegue
+    /// verificar sozinho.
+    pub fn parse_checked() -> Cli {
+        let cli = Cli::parse();
+
+        if let Some(Err(msg)) = cli.pipeline().map(PipelineArgs::check) {
+            Cli::command().error(ErrorKind::ArgumentConflict, msg).exit();
+        }
+
+        cli
+    }
+
+    /// As opções dos cálculos do subcomando escolhido.
+    fn pipeline(&self) -> Option<&PipelineArgs> {
+        match &self.command {
+            Some(Command::Analyze(args)) => Some(&args.pipeline),
+            Some(Command::Export(args)) => Some(&args.pipeline),
+            Some(Command::Convergence(args)) => Some(&args.pipeline),
+            #[cfg(feature = "viewer")]
+            Some(Command::View(args)) => Some(&args.pipeline),
+            #[cfg(feature = "viewer")]
+            None => Some(&self.view.pipeline),
+            #[cfg(not(feature = "viewer"))]
+            None => None,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Abre a malha no visualizador (padrão).
+    #[cfg(feature = "viewer")]
+    View(ViewArgs),
+
+    /// Calcula as curvaturas e imprime estatísticas, sem abrir janela.
+    Analyze(AnalyzeArgs),
+
+    /// Calcula as curvaturas e as salva em PLY, CSV ou VTK.
+    Export(ExportArgs),
+
+    /// Mede o erro dos estimadores numa superfície conhecida, em resoluções
+    /// crescentes.
+    Convergence(ConvergenceArgs),
+}
+
+#[cfg(feature = "viewer")]
+#[derive(Args, Debug)]
+pub struct ViewArgs {
+    /// Arquivo `.obj` com a malha triangular a ser visualizada.
+    #[arg(default_value = "res/models/suzanne.obj")]
+    pub mesh: PathBuf,
+
+    /// Grandeza pintada sobre a malha.
+    #[arg(
+        short,
+        long,
+        default_value = "gaussian",
+        value_parser = quantity_parser(),
+    )]
+    pub quantity: Quantity,
+
+    /// Mapa de cores. O `coolwarm` é divergente, e deixa o zero no meio.
+    #[arg(
+        short,
+        long,
+        default_value = "coolwarm",
+        value_parser = colormap_parser(),
+    )]
+    pub colormap: Colormap,
+
+    /// Intervalo de valores coberto pelo mapa de cores: do menor ao maior
+    /// valor, simétrico em volta do zero, ou do percentil 2 ao 98.
+    #[arg(
+        long,
+        default_value = "percentile",
+        value_parser = range_mode_parser(),
+    )]
+    pub range: RangeMode,
+
+    /// Largura inicial da janela, em pixels.
+    #[arg(long, default_value_t = 1280)]
+    pub width: u32,
+
+    /// Altura inicial da janela, em pixels.
+    #[arg(long, default_value_t = 720)]
+    pub height: u32,
+
+    /// Velocidade de rotação do modelo, em rad/s.
+    #[arg(short = 's', long, default_value_t = 0.3)]
+    pub rotation_speed: f32,
+
+    #[command(flatten)]
+    pub pipeline: PipelineArgs,
+}
+
+#[cfg(feature = "viewer")]
+impl ViewArgs {
+    /// Converte os argumentos nas opções do visualizador.
+    pub fn viewer_options(&self) -> ViewerOptions {
+        ViewerOptions {
+            mesh_path: self.mesh.clone(),
+            quantity: self.quantity,
+            colormap: self.colormap,
+            range_mode: self.range,
+            width: self.width,
+            height: self.height,
+            rotation_speed: self.rotation_speed,
+            pipeline: self.pipeline.options(),
+        }
+    }
+}
+
+#[cfg(feature = "viewer")]
+fn quantity_parser() -> impl TypedValueParser<Value = Quantity> {
+    PossibleValuesParser::new(Quantity::ALL.map(|q| q.name()))
+        .map(|s| s.parse::<Quantity>().unwrap())
+}
+
+#[cfg(feature = "viewer")]
+fn colormap_parser() -> impl TypedValueParser<Value = Colormap> {
+    PossibleValuesParser::new(Colormap::ALL.map(|c| c.name()))
+        .map(|s| s.parse::<Colormap>().unwrap())
+}
+
+#[cfg(feature = "viewer")]
+fn range_mode_parser() -> impl TypedValueParser<Value = RangeMode> {
+    PossibleValuesParser::new(RangeMode::ALL.map(|m| m.name()))
+        .map(|s| s.parse::<RangeMode>().unwrap())
+}
+
+/// Opções dos cálculos, comuns a todos os subcomandos.
+#[derive(Args, Debug)]
+pub struct PipelineArgs {
+    /// Estimador da curvatura gaussiana.
+    #[arg(long, value_enum, default_value_t = GaussianEstimatorArg::Paraboloid)]
+    pub gaussian_estimator: GaussianEstimatorArg,
+
+    /// Estimador da curvatura média.
+    #[arg(long, value_enum, default_value_t = MeanEstimatorArg::Paraboloid)]
+    pub mean_estimator: MeanEstimatorArg,
+
+    /// Ponderação dos vizinhos no ajuste do parabolóide.
+    #[arg(long, value_enum, default_value_t = FitWeightingArg::Uniform)]
+    pub fit_weighting: FitWeightingArg,
+
+    /// Como resolver os ajustes do parabolóide.
+    #[arg(long, value_enum, default_value_t = FitSolverArg::Scalar)]
+    pub solver: FitSolverArg,
+
+    /// Precisão das contas. Em precisão dupla, os ajustes são resolvidos
+    /// um vértice de cada vez, e `--solver simd` é recusado.
+    #[arg(long, value_enum, default_value_t = PrecisionArg::Single)]
+    pub precision: PrecisionArg,
+
+    /// Normais dos vértices: média das normais do arquivo (ou das faces,
+    /// se o arquivo não tiver normais), média ponderada das normais das
+    /// faces, ou PCA da vizinhança.
+    #[arg(long, value_enum, default_value_t = NormalSourceArg::File)]
+    pub normals: NormalSourceArg,
+
+    /// Tratamento dos vértices do bordo de malhas abertas.
+    #[arg(long, value_enum, default_value_t = BoundaryHandlingArg::Flag)]
+    pub boundary: BoundaryHandlingArg,
+
+    /// Usa no ajuste do parabolóide os vértices a no máximo K arestas de
+    /// distância.
+    #[arg(
+        long,
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["ball_radius", "geodesic_radius"],
+    )]
+    pub rings: u32,
+
+    /// Usa no ajuste do parabolóide os vértices a distância euclidiana no
+    /// máximo R.
+    #[arg(
+        long,
+        value_name = "R",
+        value_parser = parse_radius,
+        conflicts_with = "geodesic_radius",
+    )]
+    pub ball_radius: Option<f32>,
+
+    /// Usa no ajuste do parabolóide os vértices a distância geodésica
+    /// (aproximada) no máximo R.
+    #[arg(long, value_name = "R", value_parser = parse_radius)]
+    pub geodesic_radius: Option<f32>,
+}
+
+/// Lê um raio de vizinhança, que deve ser finito e positivo.
+fn parse_radius(s: &str) -> Result<f32, String> {
+    let r = s.parse::<f32>().map_err(|e| e.to_string())?;
+
+    if r.is_finite() && r > 0.0 {
+        Ok(r)
+    } else {
+        Err("the radius must be positive and finite".to_string())
+    }
+}
+
+impl PipelineArgs {
+    /// Verifica as combinações de opções que dependem dos valores.
+    fn check(&self) -> Result<(), &'static str> {
+        if self.precision == PrecisionArg::Double && self.solver == FitSolverArg::Simd {
+            return Err("`--precision double` solves the fits one vertex at a time \
+                        and cannot be combined with `--solver simd`");
+        }
+
+        Ok(())
+    }
+
+    /// Converte os argumentos nas opções do pipeline.
+    pub fn options(&self) -> PipelineOptions {
+        PipelineOptions {
+            gaussian: self.gaussian_estimator.into(),
+            mean: self.mean_estimator.into(),
+            fit_weighting: self.fit_weighting.into(),
+            solver: self.solver.into(),
+            precision: self.precision.into(),
+            neighborhood: self.neighborhood(),
+            normals: self.normals.into(),
+            boundary: self.boundary.into(),
+        }
+    }
+
+    fn neighborhood(&self) -> Neighborhood {
+        match (self.ball_radius, self.geodesic_radius) {
+            (Some(r), _) => Neighborhood::Ball(r),
+            (_, Some(r)) => Neighborhood::Geodesic(r),
+            _ => Neighborhood::Ring(self.rings as usize),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GaussianEstimatorArg {
+    /// Ajuste de parabolóide (determinante do Shape Operator).
+    Paraboloid,
+    /// Defeito angular sobre a área de Voronoi mista.
+    AngleDefect,
+}
+
+impl From<GaussianEstimatorArg> for GaussianEstimator {
+    fn from(e: GaussianEstimatorArg) -> Self {
+        match e {
+            GaussianEstimatorArg::Paraboloid => GaussianEstimator::Paraboloid,
+            GaussianEstimatorArg::AngleDefect => GaussianEstimator::AngleDefect,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeanEstimatorArg {
+    /// Ajuste de parabolóide (traço do Shape Operator).
+    Paraboloid,
+    /// Fórmula das cotangentes (Laplace-Beltrami discreto).
+    Cotangent,
+}
+
+impl From<MeanEstimatorArg> for MeanEstimator {
+    fn from(e: MeanEstimatorArg) -> Self {
+        match e {
+            MeanEstimatorArg::Paraboloid => MeanEstimator::Paraboloid,
+            MeanEstimatorArg::Cotangent => MeanEstimator::Cotangent,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FitWeightingArg {
+    /// Todos os vizinhos com o mesmo peso.
+    Uniform,
+    /// Peso inversamente proporcional ao quadrado da distância.
+    Distance,
+    /// Peso proporcional à área do vizinho.
+    Area,
+}
+
+impl From<FitWeightingArg> for FitWeighting {
+    fn from(w: FitWeightingArg) -> Self {
+        match w {
+            FitWeightingArg::Uniform => FitWeighting::Uniform,
+            FitWeightingArg::Distance => FitWeighting::InverseDistance,
+            FitWeightingArg::Area => FitWeighting::Area,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FitSolverArg {
+    /// Um vértice de cada vez.
+    Scalar,
+    /// Oito vértices de cada vez, com instruções SIMD.
+    Simd,
+}
+
+impl From<FitSolverArg> for FitSolver {
+    fn from(s: FitSolverArg) -> Self {
+        match s {
+            FitSolverArg::Scalar => FitSolver::Scalar,
+            FitSolverArg::Simd => FitSolver::Simd,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrecisionArg {
+    /// Precisão simples (f32).
+    Single,
+    /// Precisão dupla (f64).
+    Double,
+}
+
+impl From<PrecisionArg> for Precision {
+    fn from(p: PrecisionArg) -> Self {
+        match p {
+            PrecisionArg::Single => Precision::Single,
+            PrecisionArg::Double => Precision::Double,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NormalSourceArg {
+    /// Média das normais do arquivo `.obj`.
+    File,
+    /// Média simples das normais das faces.
+    Uniform,
+    /// Média das normais das faces ponderada pela área.
+    Area,
+    /// Média das normais das faces ponderada pelo ângulo no vértice.
+    Angle,
+    /// Média das normais das faces com os pesos de Max.
+    Max,
+    /// Análise de componentes principais da vizinhança.
+    Pca,
+}
+
+impl From<NormalSourceArg> for NormalSource {
+    fn from(n: NormalSourceArg) -> Self {
+        match n {
+            NormalSourceArg::File => NormalSource::File,
+            NormalSourceArg::Uniform => NormalSource::Geometry(NormalWeighting::Uniform),
+            NormalSourceArg::Area => NormalSource::Geometry(NormalWeighting::Area),
+            NormalSourceArg::Angle => NormalSource::Geometry(NormalWeighting::Angle),
+            NormalSourceArg::Max => NormalSource::Geometry(NormalWeighting::Max),
+            NormalSourceArg::Pca => NormalSource::Pca,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoundaryHandlingArg {
+    /// Calcula como nos vértices interiores, e só marca os do bordo.
+    Flag,
+    /// Ajusta o parabolóide com um anel a mais, do lado de dentro.
+    OneSided,
+    /// Deixa os vértices do bordo fora dos cálculos e das estatísticas.
+    Exclude,
+}
+
+impl From<BoundaryHandlingArg> for BoundaryHandling {
+    fn from(b: BoundaryHandlingArg) -> Self {
+        match b {
+            BoundaryHandlingArg::Flag => BoundaryHandling::Flag,
+            BoundaryHandlingArg::OneSided => BoundaryHandling::OneSided,
+            BoundaryHandlingArg::Exclude => BoundaryHandling::Exclude,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct AnalyzeArgs {
+    /// Arquivos `.obj` a serem analisados.
+    #[arg(required = true)]
+    pub meshes: Vec<PathBuf>,
+
+    /// Formato da saída.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    #[command(flatten)]
+    pub pipeline: PipelineArgs,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Arquivo `.obj` de entrada.
+    pub mesh: PathBuf,
+
+    /// Arquivo de saída.
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Formato do arquivo de saída. Se omitido, é deduzido pela extensão
+    /// de `--output`.
+    #[arg(short, long, value_enum)]
+    pub format: Option<ExportFormat>,
+
+    /// Índice do modelo dentro do arquivo `.obj`.
+    #[arg(long, default_value_t = 0)]
+    pub model: usize,
+
+    #[command(flatten)]
+    pub pipeline: PipelineArgs,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Ply,
+    Csv,
+    Vtk,
+    Vtu,
+}
+
+impl From<ExportFormat> for gauss::export::Format {
+    fn from(f: ExportFormat) -> Self {
+        match f {
+            ExportFormat::Ply => gauss::export::Format::Ply,
+            ExportFormat::Csv => gauss::export::Format::Csv,
+            ExportFormat::Vtk => gauss::export::Format::Vtk,
+            ExportFormat::Vtu => gauss::export::Format::Vtu,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ConvergenceArgs {
+    /// Superfície de teste.
+    #[arg(long, value_enum, default_value_t = TestSurfaceArg::Sphere)]
+    pub surface: TestSurfaceArg,
+
+    /// Resoluções a testar, separadas por vírgulas. Elas são testadas em
+    /// ordem crescente, e as repetidas são ignoradas.
+    #[arg(
+        short,
+        long,
+        value_delimiter = ',',
+        default_value = "8,16,32,64",
+        value_parser = clap::value_parser!(u32).range(2..),
+    )]
+    pub resolutions: Vec<u32>,
+
+    /// Também salva a tabela neste arquivo CSV.
+    #[arg(long, value_name = "FILE")]
+    pub csv: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub pipeline: PipelineArgs,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestSurfaceArg {
+    /// Esfera de raio 1.
+    Sphere,
+    /// Toro de raios 2 e 0.5.
+    Torus,
+    /// Cilindro aberto de raio 1 e altura 2.
+    Cylinder,
+    /// Elipsóide de semi-eixos 1, 1.5 e 2.
+    Ellipsoid,
+    /// Parabolóide hiperbólico z = (x² - y²)/2.
+    HyperbolicParaboloid,
+    /// Catenóide de cintura 1 e altura 2.
+    Catenoid,
+    /// Helicóide de passo 0.5 e raio 1.
+    Helicoid,
+}
+
+impl From<TestSurfaceArg> for TestSurface {
+    fn from(s: TestSurfaceArg) -> Self {
+        match s {
+            TestSurfaceArg::Sphere => TestSurface::Sphere,
+            TestSurfaceArg::Torus => TestSurface::Torus,
+            TestSurfaceArg::Cylinder => TestSurface::Cylinder,
+            TestSurfaceArg::Ellipsoid => TestSurface::Ellipsoid,
+            TestSurfaceArg::HyperbolicParaboloid => TestSurface::HyperbolicParaboloid,
+            TestSurfaceArg::Catenoid => TestSurface::Catenoid,
+            TestSurfaceArg::Helicoid => TestSurface::Helicoid,
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! # Estudo de convergência
+//!
+//! A documentação de [`crate::geom`] diz que, quanto maior a resolução da
+//! malha, mais perto dos valores corretos ficam as curvaturas. Esse módulo
+//! mede isso: gera uma superfície de [`crate::surfaces`] em resoluções
+//! crescentes, roda cada estimador disponível, e compara o resultado com
+//! as curvaturas exatas.
+//!
+//! Para cada estimador e resolução calculamos as normas L2 e L∞ do erro
+//! (ver [`stats::error_norms`]) e a *ordem de convergência observada*
+//! entre duas resoluções consecutivas,
+//!
+//! $$ p = \frac{\log(e_{i-1} / e_i)}{\log(h_{i-1} / h_i)}, $$
+//!
+//! onde $h$ é o comprimento médio das arestas da malha. Se o erro se
+//! comporta como $C h^p$, dobrar a resolução divide o erro por $2^p$.
+//!
+//! Os vértices do bordo ficam fora das contas: os estimadores discretos
+//! não estão definidos neles, e o ajuste do parabolóide ali é sempre pior.
+
+use std::io::{self, Write};
+
+use crate::{
+    discrete, double,
+    geom::{self, GeomError},
+    halfedge::HalfEdgeMesh,
+    pipeline::{GaussianEstimator, MeanEstimator, PipelineOptions, VertexAttributes},
+    stats::{self, ErrorNorms},
+    surfaces::{self, AnalyticSurface},
+};
+
+/// As superfícies de [`crate::surfaces`] disponíveis para o estudo, com
+/// dimensões fixas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestSurface {
+    /// Esfera de raio 1.
+    Sphere,
+    /// Toro de raios 2 e 0.5.
+    Torus,
+    /// Cilindro aberto de raio 1 e altura 2.
+    Cylinder,
+    /// Elipsóide de semi-eixos 1, 1.5 e 2.
+    Ellipsoid,
+    /// Parabolóide hiperbólico $z = (x^2 - y^2)/2$ sobre $[-1, 1]^2$.
+    HyperbolicParaboloid,
+    /// Catenóide de cintura 1 e altura 2.
+    Catenoid,
+    /// Helicóide de passo 0.5 e raio 1, com uma volta.
+    Helicoid,
+}
+
+impl TestSurface {
+    pub const ALL: [TestSurface; 7] = [
+        TestSurface::Sphere,
+        TestSurface::Torus,
+        TestSurface::Cylinder,
+        TestSurface::Ellipsoid,
+        TestSurface::HyperbolicParaboloid,
+        TestSurface::Catenoid,
+        TestSurface::Helicoid,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TestSurface::Sphere => "sphere",
+            TestSurface::Torus => "torus",
+            TestSurface::Cylinder => "cylinder",
+            TestSurface::Ellipsoid => "ellipsoid",
+            TestSurface::HyperbolicParaboloid => "hyperbolic-paraboloid",
+            TestSurface::Catenoid => "catenoid",
+            TestSurface::Helicoid => "helicoid",
+        }
+    }
+
+    /// Gera a superfície com resolução `n` (ver [`crate::surfaces`]).
+    pub fn generate(self, n: usize) -> AnalyticSurface {
+        match self {
+            TestSurface::Sphere => surfaces::sphere(1.0, n),
+            TestSurface::Torus => surfaces::torus(2.0, 0.5, n),
+            TestSurface::Cylinder => surfaces::cylinder(1.0, 2.0, n),
+            TestSurface::Ellipsoid => surfaces::ellipsoid(1.0, 1.5, 2.0, n),
+            TestSurface::HyperbolicParaboloid => surfaces::hyperbolic_paraboloid(0.5, 2.0, n),
+            TestSurface::Catenoid => surfaces::catenoid(1.0, 2.0, n),
+            TestSurface::Helicoid => surfaces::helicoid(0.5, 1.0, 1.0, n),
+        }
+    }
+}
+
+/// Um estimador de uma das curvaturas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Estimator {
+    /// K pelo ajuste do parabolóide.
+    ParaboloidGaussian,
+    /// H pelo ajuste do parabolóide.
+    ParaboloidMean,
+    /// κ1 pelo ajuste do parabolóide.
+    ParaboloidK1,
+    /// κ2 pelo ajuste do parabolóide.
+    ParaboloidK2,
+    /// K pelo defeito angular.
+    AngleDefect,
+    /// H pela fórmula das cotangentes.
+    Cotangent,
+}
+
+impl Estimator {
+    pub const ALL: [Estimator; 6] = [
+        Estimator::ParaboloidGaussian,
+        Estimator::AngleDefect,
+        Estimator::ParaboloidMean,
+        Estimator::Cotangent,
+        Estimator::ParaboloidK1,
+        Estimator::ParaboloidK2,
+    ];
+
+    /// Nome da curvatura estimada: `K`, `H`, `k1` ou `k2`.
+    pub fn quantity(self) -> &'static str {
+        match self {
+            Estimator::ParaboloidGaussian | Estimator::AngleDefect => "K",
+            Estimator::ParaboloidMean | Estimator::Cotangent => "H",
+            Estimator::ParaboloidK1 => "k1",
+            Estimator::ParaboloidK2 => "k2",
+        }
+    }
+
+    /// Nome do método.
+    pub fn method(self) -> &'static str {
+        match self {
+            Estimator::ParaboloidGaussian
+            | Estimator::ParaboloidMean
+            | Estimator::ParaboloidK1
+            | Estimator::ParaboloidK2 => "paraboloid",
+            Estimator::AngleDefect => "angle-defect",
+            Estimator::Cotangent => "cotangent",
+        }
+    }
+}
+
+/// Resultado de um estimador numa resolução.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConvergenceRow {
+    pub surface: TestSurface,
+    pub estimator: Estimator,
+    /// Resolução passada ao gerador da superfície.
+    pub resolution: usize,
+    pub vertices: usize,
+    /// Comprimento médio das arestas.
+    pub edge_length: f32,
+    /// Normas do erro, ou `None` se nenhum vértice pôde ser comparado.
+    pub error: Option<ErrorNorms>,
+    /// Ordem observada na norma L2, em relação à resolução anterior.
+    pub order_l2: Option<f32>,
+    /// Ordem observada na norma L∞, em relação à resolução anterior.
+    pub order_linf: Option<f32>,
+}
+
+/// Roda o estudo de convergência de `surface` nas resoluções dadas.
+///
+/// `opts` controla o ajuste do parabolóide (vizinhança, pesos, normais) e
+/// a precisão de todos os estimadores; os estimadores escolhidos nele são
+/// ignorados, já que todos são avaliados. As linhas saem agrupadas por estimador, na ordem de
+/// [`Estimator::ALL`], e dentro de cada grupo na ordem de `resolutions`.
+pub fn study(
+    surface: TestSurface,
+    resolutions: &[usize],
+    opts: &PipelineOptions,
+) -> Result<Vec<ConvergenceRow>, GeomError> {
+    let opts = PipelineOptions {
+        gaussian: GaussianEstimator::Paraboloid,
+        mean: MeanEstimator::Paraboloid,
+        ..*opts
+    };
+
+    let mut levels = Vec::with_capacity(resolutions.len());
+
+    for &n in resolutions {
+        let exact = surface.generate(n);
+        let mesh = HalfEdgeMesh::new(&exact.mesh)?;
+        let attrs = VertexAttributes::compute_on(&mesh, &opts);
+        let areas = geom::compute_vertex_areas(&mesh);
+
+        let (angle_defect, cotangent): (Vec<_>, Vec<_>) = match opts.precision {
+            geom::Precision::Single => (
+                discrete::compute_angle_defect_curvatures(&mesh),
+                discrete::compute_cotangent_mean_curvatures(&mesh).into_iter().map(|(h, _)| h).collect(),
+            ),
+            geom::Precision::Double => (
+                double::compute_angle_defect_curvatures(&mesh).into_iter().map(|k| k as f32).collect(),
+                double::compute_cotangent_mean_curvatures(&mesh).into_iter().map(|(h, _)| h as f32).collect(),
+            ),
+        };
+
+        let errors = Estimator::ALL.map(|estimator| {
+            let (estimate, truth) = match estimator {
+                Estimator::ParaboloidGaussian => (&attrs.gaussian, &exact.gaussian),
+                Estimator::ParaboloidMean => (&attrs.mean, &exact.mean),
+                Estimator::ParaboloidK1 => (&attrs.k1, &exact.k1),
+                Estimator::ParaboloidK2 => (&attrs.k2, &exact.k2),
+                Estimator::AngleDefect => (&angle_defect, &exact.gaussian),
+                Estimator::Cotangent => (&cotangent, &exact.mean),
+            };
+
+            stats::error_norms(estimate, truth, &areas, &attrs.boundary)
+        });
+
+        levels.push((n, mesh.n_vertices(), mean_edge_length(&mesh), errors));
+    }
+
+    let mut rows = Vec::with_capacity(levels.len() * Estimator::ALL.len());
+
+    for (e, &estimator) in Estimator::ALL.iter().enumerate() {
+        for (i, &(resolution, vertices, edge_length, errors)) in levels.iter().enumerate() {
+            let error = errors[e];
+            let prev = i.checked_sub(1).map(|j| (levels[j].2, levels[j].3[e]));
+
+            let order = |norm: fn(&ErrorNorms) -> f32| {
+                let (prev_h, prev_error) = prev?;
+                let order = (norm(&prev_error?) / norm(&error?)).ln() / (prev_h / edge_length).ln();
+
+                order.is_finite().then_some(order)
+            };
+
+            rows.push(ConvergenceRow {
+                surface,
+                estimator,
+                resolution,
+                vertices,
+                edge_length,
+                error,
+                order_l2: order(|e| e.l2),
+                order_linf: order(|e| e.linf),
+            });
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Comprimento médio das arestas da malha.
+fn mean_edge_length(mesh: &HalfEdgeMesh) -> f32 {
+    // Cada aresta interior aparece em duas semi-arestas, e cada aresta do
+    // bordo em uma só. Para contar cada aresta uma vez, ficamos com a
+    // semi-aresta de menor índice (ou com a única, no bordo).
+    let (total, count) = (0..mesh.n_halfedges())
+        .filter(|&h| mesh.twin(h).is_none_or(|twin| h < twin))
+        .map(|h| (mesh.position(mesh.target(h)) - mesh.position(mesh.origin(h))).mag() as f64)
+        .fold((0.0, 0usize), |(total, count), len| (total + len, count + 1));
+
+    (total / count as f64) as f32
+}
+
+/// Escreve as linhas do estudo como uma tabela CSV.
+///
+/// Valores ausentes (ordens da primeira resolução, ou erros que não
+/// puderam ser calculados) ficam vazios.
+pub fn write_csv<W: Write>(w: &mut W, rows: &[ConvergenceRow]) -> io::Result<()> {
+    writeln!(w, "surface,quantity,method,resolution,vertices,edge_length,samples,l2,linf,order_l2,order_linf")?;
+
+    let opt = |v: Option<f32>| v.map(|v| v.to_string()).unwrap_or_default();
+
+    for row in rows {
+        writeln!(
+            w,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            row.surface.name(),
+            row.estimator.quantity(),
+            row.estimator.method(),
+            row.resolution,
+            row.vertices,
+            row.edge_length,
+            row.error.map_or(0, |e| e.count),
+            opt(row.error.map(|e| e.l2)),
+            opt(row.error.map(|e| e.linf)),
+            opt(row.order_l2),
+            opt(row.order_linf),
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ultraviolet::Vec3;
+
+    use super::*;
+    use crate::mesh::Mesh;
+
+    #[test]
+    fn edges_are_counted_once() {
+        // Um quadrado de lado 1 cortado na diagonal: quatro arestas no
+        // bordo e só a diagonal no interior.
+        let positions = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        let mesh = HalfEdgeMesh::new(&Mesh::new(positions, vec![[0, 1, 2], [0, 2, 3]])).unwrap();
+
+        let expected = (4.0 + 2.0f32.sqrt()) / 5.0;
+        assert!((mean_edge_length(&mesh) - expected).abs() < 1e-6, "{}", mean_edge_length(&mesh));
+    }
+
+    /// Ordens observadas na norma L2 do defeito angular na esfera.
+    fn angle_defect_orders(resolutions: &[usize], precision: geom::Precision) -> Vec<f32> {
+        let opts = PipelineOptions { precision, ..Default::default() };
+
+        study(TestSurface::Sphere, resolutions, &opts)
+            .unwrap()
+            .iter()
+            .filter(|row| row.estimator == Estimator::AngleDefect)
+            .filter_map(|row| row.order_l2)
+            .collect()
+    }
+
+    #[test]
+    fn angle_defect_converges_quadratically_on_sphere() {
+        let orders = angle_defect_orders(&[16, 32, 64], geom::Precision::Single);
+
+        assert_eq!(orders.len(), 2);
+        for order in orders {
+            assert!((order - 2.0).abs() < 0.2, "order {}", order);
+        }
+    }
+
+    #[test]
+    fn double_precision_keeps_the_order() {
+        // De 64 para 128, o arredondamento em f32 do defeito angular passa a
+        // dominar o erro, e a ordem cai. Em f64 ela continua perto de 2.
+        let single = angle_defect_orders(&[64, 128], geom::Precision::Single);
+        let double = angle_defect_orders(&[64, 128], geom::Precision::Double);
+
+        assert!(single[0] < 1.5, "order {}", single[0]);
+        assert!((double[0] - 2.0).abs() < 0.2, "order {}", double[0]);
+    }
+}
@@ -0,0 +1,90 @@
+//! # Estudo de convergência pela linha de comando
+//!
+//! Implementação do subcomando `gauss convergence`, que roda o estudo de
+//! [`gauss::convergence`] e imprime uma tabela com os erros e as ordens
+//! observadas, opcionalmente salvando-a em CSV.
+
+use std::{fs::File, io::{BufWriter, Write}};
+
+use gauss::convergence::{self, ConvergenceRow, TestSurface};
+
+use crate::cli::ConvergenceArgs;
+
+/// Roda o estudo e imprime os resultados.
+///
+/// Erros são impressos em `stderr`. Retorna `false` se algo falhou.
+pub fn run(args: &ConvergenceArgs) -> bool {
+    let surface = TestSurface::from(args.surface);
+
+    // As ordens observadas comparam cada resolução com a anterior, então
+    // elas precisam estar em ordem crescente e sem repetições.
+    let mut resolutions: Vec<_> = args.resolutions.iter().map(|&n| n as usize).collect();
+    resolutions.sort_unstable();
+    resolutions.dedup();
+
+    let rows = match convergence::study(surface, &resolutions, &args.pipeline.options()) {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("failed to process {}: {}", surface.name(), e);
+            return false;
+        }
+    };
+
+    print_table(surface, &rows);
+
+    if let Some(path) = &args.csv {
+        let written = File::create(path).and_then(|f| {
+            let mut w = BufWriter::new(f);
+            convergence::write_csv(&mut w, &rows)?;
+            w.flush()
+        });
+
+        if let Err(e) = written {
+            eprintln!("failed to write {}: {}", path.display(), e);
+            return false;
+        }
+    }
+
+    true
+}
+
+fn print_table(surface: TestSurface, rows: &[ConvergenceRow]) {
+    println!("{}", surface.name());
+    println!(
+        "  {:<2} {:<12} {:>5} {:>9} {:>10} {:>11} {:>6} {:>11} {:>6}",
+        "", "method", "n", "vertices", "h", "L2", "order", "Linf", "order",
+    );
+
+    let order = |p: Option<f32>| p.map(|p| format!("{:.2}", p)).unwrap_or_default();
+
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 && rows[i - 1].estimator != row.estimator {
+            println!();
+        }
+
+        match row.error {
+            Some(e) => println!(
+                "  {:<2} {:<12} {:>5} {:>9} {:>10.4e} {:>11.4e} {:>6} {:>11.4e} {:>6}",
+                row.estimator.quantity(),
+                row.estimator.method(),
+                row.resolution,
+                row.vertices,
+                row.edge_length,
+                e.l2,
+                order(row.order_l2),
+                e.linf,
+                order(row.order_linf),
+            ),
+            None => println!(
+                "  {:<2} {:<12} {:>5} {:>9} {:>10.4e} (no finite values)",
+                row.estimator.quantity(),
+                row.estimator.method(),
+                row.resolution,
+                row.vertices,
+                row.edge_length,
+            ),
+        }
+    }
+
+    println!();
+}
@@ -0,0 +1,444 @@
+//! # Operadores discretos
+//!
+//! Esse módulo contém uma segunda família de estimadores de curvatura,
+//! que não tenta recuperar uma parametrização local da superfície como
+//! em [`crate::geom`]. Em vez disso, usamos diretamente a topologia e os
+//! ângulos da malha triangular, seguindo o artigo de Meyer, Desbrun,
+//! Schröder e Barr incluído em `res/curvature-in-triangle-meshes.pdf`.
+//!
+//! A ideia é olhar para cada vértice como uma pequena região da superfície
+//! (a sua *área de Voronoi mista*, ver [`compute_mixed_areas`]) e calcular
+//! a integral das curvaturas sobre essa região. Dividindo pela área, temos
+//! uma média da curvatura perto do vértice.
+//!
+//! Para a curvatura gaussiana, a integral vem do teorema de Gauss-Bonnet:
+//! num vértice interior, a integral de K sobre a região é o *defeito
+//! angular* $2\pi - \sum_j \theta_j$, onde os $\theta_j$ são os ângulos dos
+//! triângulos incidentes no vértice. Numa superfície plana esses ângulos
+//! somam exatamente $2\pi$, e o defeito é zero.
+//!
+//! Para a curvatura média, usamos o operador de Laplace-Beltrami discreto
+//! (a fórmula das cotangentes). Aplicado às posições dos vértices, ele dá
+//! o *vetor curvatura média* $2 H N$, de onde tiramos tanto a direção
+//! normal quanto o valor de H.
+//!
+//! ## Bordo
+//!
+//! Nos vértices do bordo de uma malha aberta, os triângulos em volta do
+//! vértice só cobrem um "leque", e as duas fórmulas acima deixam de valer:
+//! a soma dos ângulos de um vértice do bordo de uma região plana é $\pi$,
+//! e não $2\pi$, e falta metade das arestas na fórmula das cotangentes.
+//! Nesses vértices as curvaturas ficam `NaN`.
+//!
+//! Em compensação, o que sobra do defeito angular num vértice do bordo,
+//! $\pi - \sum_j \theta_j$, é o quanto o bordo "vira" ali: a integral da
+//! *curvatura geodésica* do bordo perto do vértice (ver
+//! [`compute_boundary_turning_angles`]). Com ela temos o teorema de
+//! Gauss-Bonnet para superfícies com bordo,
+//!
+//! $$\int_S K \, dA + \int_{\partial S} k_g \, ds = 2 \pi \chi(S),$$
+//!
+//! que, assim como no caso fechado, vale exatamente para as versões
+//! discretas (ver [`compute_boundary_loops`]).
+
+use std::f32::consts::PI;
+
+use ultraviolet::Vec3;
+
+use crate::{halfedge::HalfEdgeMesh, par};
+
+/// Cotangente do ângulo entre `u` e `v`.
+fn cot(u: Vec3, v: Vec3) -> f32 {
+    u.dot(v) / u.cross(v).mag()
+}
+
+/// Calcula os ângulos internos de cada triângulo.
+///
+/// O elemento `i` contém os ângulos nos três cantos da face `i`, na mesma
+/// ordem dos vértices da face.
+pub fn compute_corner_angles(mesh: &HalfEdgeMesh) -> Vec<[f32; 3]> {
+    par::map(mesh.n_faces(), |f| {
+        let p = mesh.face_positions(f);
+
+        [0, 1, 2].map(|k| {
+            let u = p[(k + 1) % 3] - p[k];
+            let v = p[(k + 2) % 3] - p[k];
+
+            u.normalized().dot(v.normalized()).clamp(-1.0, 1.0).acos()
+        })
+    })
+}
+
+/// Calcula a área de Voronoi mista de cada vértice.
+///
+/// A região de Voronoi de um vértice $p$ dentro de um triângulo é o
+/// conjunto de pontos do triângulo mais próximos de $p$ do que dos outros
+/// dois vértices. Se o triângulo não é obtuso, a área dessa região é
+///
+/// $$\frac{1}{8} \left( |pr|^2 \cot q + |pq|^2 \cot r \right)$$
+///
+/// onde $q$ e $r$ são os outros dois vértices. Em triângulos obtusos a
+/// região de Voronoi sai do triângulo, e então usamos metade da área do
+/// triângulo se o ângulo obtuso está em $p$, e um quarto caso contrário.
+///
+/// Essas áreas cobrem a malha inteira sem sobreposição, e portanto somam
+/// a área total da malha.
+pub fn compute_mixed_areas(mesh: &HalfEdgeMesh) -> Vec<f32> {
+    let angles = compute_corner_angles(mesh);
+
+    // A contribuição do canto `c` para a área do seu vértice.
+    let corner_area = |c: usize| {
+        let f = mesh.face(c);
+        let p = mesh.face_positions(f);
+
+        let area = 0.5 * (p[1] - p[0]).cross(p[2] - p[0]).mag();
+        let obtuse = angles[f].iter().position(|&a| a > 0.5 * PI);
+
+        let k = c % 3;
+        let (q, r) = ((k + 1) % 3, (k + 2) % 3);
+
+        match obtuse {
+            None => {
+                let cot_q = cot(p[k] - p[q], p[r] - p[q]);
+                let cot_r = cot(p[k] - p[r], p[q] - p[r]);
+
+                0.125 * ((p[r] - p[k]).mag_sq() * cot_q + (p[q] - p[k]).mag_sq() * cot_r)
+            }
+            Some(o) if o == k => 0.5 * area,
+            Some(_) => 0.25 * area,
+        }
+    };
+
+    par::map(mesh.n_vertices(), |v| mesh.corners(v).map(corner_area).fold(0.0, |acc, a| acc + a))
+}
+
+/// Soma dos ângulos internos dos triângulos em volta de cada vértice.
+fn compute_angle_sums(mesh: &HalfEdgeMesh) -> Vec<f32> {
+    let angles = compute_corner_angles(mesh);
+
+    par::map(mesh.n_vertices(), |v| {
+        mesh.corners(v)
+            .map(|c| angles[mesh.face(c)][c % 3])
+            .fold(0.0, |acc, a| acc + a)
+    })
+}
+
+/// Calcula o defeito angular $2\pi - \sum_j \theta_j$ de cada vértice.
+///
+/// Esse valor é a integral da curvatura gaussiana sobre a região de
+/// Voronoi do vértice. Vértices que não aparecem em nenhuma face ficam
+/// com defeito zero, e vértices do bordo ficam com `NaN` (o defeito deles
+/// está em [`compute_boundary_turning_angles`]).
+pub fn compute_angle_defects(mesh: &HalfEdgeMesh) -> Vec<f32> {
+    compute_angle_sums(mesh)
+        .into_iter()
+        .enumerate()
+        .map(|(v, sum)| {
+            if mesh.is_isolated(v) {
+                0.0
+            } else if mesh.is_boundary_vertex(v) {
+                f32::NAN
+            } else {
+                2.0 * PI - sum
+            }
+        })
+        .collect()
+}
+
+/// Calcula o ângulo de giro $\pi - \sum_j \theta_j$ do bordo em cada
+/// vértice do bordo.
+///
+/// Esse valor é a integral da curvatura geodésica do bordo sobre a região
+/// do vértice. Ele é positivo onde o bordo vira para o lado da superfície
+/// (como no bordo de um disco) e negativo onde vira para fora. Vértices
+/// interiores ficam com zero.
+pub fn compute_boundary_turning_angles(mesh: &HalfEdgeMesh) -> Vec<f32> {
+    compute_angle_sums(mesh)
+        .into_iter()
+        .enumerate()
+        .map(|(v, sum)| if mesh.is_boundary_vertex(v) { PI - sum } else { 0.0 })
+        .collect()
+}
+
+/// Calcula a curvatura geodésica do bordo em cada vértice do bordo.
+///
+/// É o ângulo de giro ([`compute_boundary_turning_angles`]) dividido pelo
+/// comprimento do bordo associado ao vértice: metade de cada uma das duas
+/// arestas do bordo que chegam nele. Vértices interiores ficam com `NaN`.
+pub fn compute_geodesic_curvatures(mesh: &HalfEdgeMesh) -> Vec<f32> {
+    let length = |h: usize| (mesh.position(mesh.target(h)) - mesh.position(mesh.origin(h))).mag();
+
+    // As arestas do bordo de um vértice são a que sai dele e a que chega
+    // nele, que é a anterior a algum dos seus cantos.
+    let lengths = par::map(mesh.n_vertices(), |v| {
+        mesh.corners(v)
+            .flat_map(|c| [c, mesh.prev(c)])
+            .filter(|&h| mesh.is_boundary_halfedge(h))
+            .map(|h| 0.5 * length(h))
+            .fold(0.0, |acc, l| acc + l)
+    });
+
+    compute_boundary_turning_angles(mesh)
+        .into_iter()
+        .zip(lengths)
+        .enumerate()
+        .map(|(v, (turning, length))| {
+            if mesh.is_boundary_vertex(v) { turning / length } else { f32::NAN }
+        })
+        .collect()
+}
+
+/// Um laço do bordo da malha.
+#[derive(Clone, Debug)]
+pub struct BoundaryLoop {
+    /// Os vértices do laço, em ordem.
+    pub vertices: Vec<usize>,
+    /// Comprimento do laço.
+    pub length: f32,
+    /// Integral da curvatura geodésica ao longo do laço, ou seja, a soma
+    /// dos ângulos de giro dos seus vértices.
+    pub total_geodesic_curvature: f32,
+}
+
+/// Encontra os laços do bordo da malha, com seus comprimentos e curvaturas
+/// geodésicas totais.
+///
+/// Somando a curvatura total do interior (a soma dos defeitos angulares) e
+/// as curvaturas geodésicas totais de todos os laços, obtemos exatamente
+/// $2\pi\chi$ (ver [`HalfEdgeMesh::euler_characteristic`]).
+pub fn compute_boundary_loops(mesh: &HalfEdgeMesh) -> Vec<BoundaryLoop> {
+    let turning = compute_boundary_turning_angles(mesh);
+
+    mesh.boundary_loops()
+        .into_iter()
+        .map(|vertices| {
+            let n = vertices.len();
+
+            let length = (0..n)
+                .map(|k| (mesh.position(vertices[(k + 1) % n]) - mesh.position(vertices[k])).mag())
+                .sum();
+            let total_geodesic_curvature = vertices.iter().map(|&v| turning[v]).sum();
+
+            BoundaryLoop {
+                vertices,
+                length,
+                total_geodesic_curvature,
+            }
+        })
+        .collect()
+}
+
+/// Calcula a curvatura gaussiana de cada vértice pelo defeito angular.
+///
+/// É o defeito angular ([`compute_angle_defects`]) dividido pela área de
+/// Voronoi mista ([`compute_mixed_areas`]). Somando $K_i A_i$ sobre todos
+/// os vértices de uma malha fechada, obtemos exatamente $2\pi\chi$: esse
+/// estimador satisfaz uma versão discreta do teorema de Gauss-Bonnet.
+///
+/// Vértices do bordo ficam com `NaN`.
+pub fn compute_angle_defect_curvatures(mesh: &HalfEdgeMesh) -> Vec<f32> {
+    let defects = compute_angle_defects(mesh);
+    let areas = compute_mixed_areas(mesh);
+
+    defects
+        .into_iter()
+        .zip(areas)
+        .map(|(defect, area)| defect / area)
+        .collect()
+}
+
+/// Calcula o vetor curvatura média $H N$ de cada vértice.
+///
+/// Pela fórmula das cotangentes, o operador de Laplace-Beltrami aplicado
+/// às posições no vértice $x_i$ é
+///
+/// $$\mathbf{K}(x_i) = \frac{1}{2 A_i} \sum_j (\cot \alpha_{ij} + \cot \beta_{ij}) (x_i - x_j)$$
+///
+/// onde a soma percorre os vizinhos $x_j$ de $x_i$, $\alpha_{ij}$ e
+/// $\beta_{ij}$ são os ângulos opostos à aresta $x_i x_j$ nos dois
+/// triângulos que a contém, e $A_i$ é a área de Voronoi mista. Como
+/// $\mathbf{K} = 2 H N$, retornamos metade desse vetor.
+///
+/// Vértices do bordo ficam com `NaN`, já que a fórmula precisa das faces
+/// dos dois lados de cada aresta.
+///
+/// Na prática, percorremos os cantos de cada vértice $x_i$: em cada face,
+/// a semi-aresta $x_i \to x_j$ que sai do vértice enxerga um dos ângulos
+/// opostos à aresta $x_i x_j$, e a semi-aresta $x_k \to x_i$ que chega
+/// nele enxerga um dos ângulos opostos à aresta $x_k x_i$. Os outros
+/// ângulos opostos a essas arestas aparecem nas faces vizinhas.
+pub fn compute_mean_curvature_normals(mesh: &HalfEdgeMesh) -> Vec<Vec3> {
+    let areas = compute_mixed_areas(mesh);
+
+    par::map(mesh.n_vertices(), |i| {
+        if mesh.is_boundary_vertex(i) {
+            return Vec3::broadcast(f32::NAN);
+        }
+
+        let xi = mesh.position(i);
+        let mut sum = Vec3::zero();
+
+        for c in mesh.corners(i) {
+            // A semi-aresta que sai de x_i (até x_j) e a que chega (de x_k).
+            let xj = mesh.position(mesh.target(c));
+            let xk = mesh.position(mesh.opposite_vertex(c));
+
+            sum += cot(xi - xk, xj - xk) * (xi - xj);
+            sum += cot(xk - xj, xi - xj) * (xi - xk);
+        }
+
+        // 1/(2A) do operador, e mais 1/2 para ir de 2HN para HN.
+        sum / (4.0 * areas[i])
+    })
+}
+
+/// Calcula a curvatura média de cada vértice pela fórmula das cotangentes.
+///
+/// Retorna, para cada vértice, o par $(H, H N)$: o valor escalar da
+/// curvatura média e o vetor curvatura média de
+/// [`compute_mean_curvature_normals`].
+///
+/// O módulo de $H N$ só nos dá $|H|$. O sinal vem de comparar o vetor com
+/// a normal do vértice, que aqui calculamos a partir da orientação das
+/// faces (a soma dos produtos vetoriais das faces incidentes). Assim o
+/// sinal só depende da ordem dos vértices nas faces e não das normais do
+/// arquivo: numa malha fechada e consistentemente orientada, H é positiva
+/// onde a superfície é convexa, como numa esfera.
+pub fn compute_cotangent_mean_curvatures(mesh: &HalfEdgeMesh) -> Vec<(f32, Vec3)> {
+    let orientations = par::map(mesh.n_vertices(), |v| {
+        mesh.corners(v)
+            .map(|c| {
+                let p = mesh.face_positions(mesh.face(c));
+
+                // O produto vetorial tem norma igual ao dobro da área, então
+                // faces maiores pesam mais.
+                (p[1] - p[0]).cross(p[2] - p[0])
+            })
+            .fold(Vec3::zero(), |acc, n| acc + n)
+    });
+
+    compute_mean_curvature_normals(mesh)
+        .into_iter()
+        .zip(orientations)
+        .map(|(hn, orientation)| {
+            let h = hn.mag().copysign(hn.dot(orientation));
+
+            (h, hn)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{geom, surfaces};
+
+    /// Soma de $K_i A_i$ sobre todos os vértices interiores.
+    fn total_curvature(mesh: &HalfEdgeMesh) -> f64 {
+        compute_angle_defect_curvatures(mesh)
+            .into_iter()
+            .zip(compute_mixed_areas(mesh))
+            .enumerate()
+            .filter(|&(v, _)| !mesh.is_boundary_vertex(v))
+            .map(|(_, (k, area))| k as f64 * area as f64)
+            .sum()
+    }
+
+    /// Soma dos ângulos de giro sobre todos os laços do bordo.
+    fn total_geodesic_curvature(mesh: &HalfEdgeMesh) -> f64 {
+        compute_boundary_loops(mesh)
+            .iter()
+            .map(|l| l.total_geodesic_curvature as f64)
+            .sum()
+    }
+
+    #[test]
+    fn gauss_bonnet_on_closed_surfaces() {
+        // A identidade é exata, mas cada defeito angular é calculado em f32,
+        // e o erro de arredondamento cresce com o número de vértices.
+        let sphere = HalfEdgeMesh::new(&surfaces::sphere(1.0, 16).mesh).unwrap();
+        let torus = HalfEdgeMesh::new(&surfaces::torus(2.0, 0.5, 24).mesh).unwrap();
+
+        assert_eq!(sphere.euler_characteristic(), 2);
+        assert_eq!(torus.euler_characteristic(), 0);
+
+        let tau = 2.0 * std::f64::consts::PI;
+
+        assert!((total_curvature(&sphere) - 2.0 * tau).abs() < 1e-4, "{}", total_curvature(&sphere));
+        assert!(total_curvature(&torus).abs() < 1e-4, "{}", total_curvature(&torus));
+    }
+
+    #[test]
+    fn angle_defect_curvature_on_sphere() {
+        // Gauss-Bonnet só olha para a soma, em que as áreas se cancelam.
+        // Aqui conferimos K em cada vértice.
+        let radius = 2.0;
+        let mesh = HalfEdgeMesh::new(&surfaces::sphere(radius, 32).mesh).unwrap();
+        let k = 1.0 / (radius * radius);
+
+        for (v, curvature) in compute_angle_defect_curvatures(&mesh).into_iter().enumerate() {
+            assert!(!mesh.is_boundary_vertex(v));
+            assert!((curvature - k).abs() < 0.02 * k, "K = {} at vertex {}", curvature, v);
+        }
+    }
+
+    #[test]
+    fn gauss_bonnet_with_boundary() {
+        let cylinder = HalfEdgeMesh::new(&surfaces::cylinder(1.0, 2.0, 16).mesh).unwrap();
+        let saddle = HalfEdgeMesh::new(&surfaces::hyperbolic_paraboloid(1.0, 2.0, 16).mesh).unwrap();
+
+        assert_eq!(cylinder.euler_characteristic(), 0);
+        assert_eq!(cylinder.boundary_loops().len(), 2);
+        assert_eq!(saddle.euler_characteristic(), 1);
+        assert_eq!(saddle.boundary_loops().len(), 1);
+
+        let tau = 2.0 * std::f64::consts::PI;
+        let total = |mesh: &HalfEdgeMesh| total_curvature(mesh) + total_geodesic_curvature(mesh);
+
+        assert!(total(&cylinder).abs() < 1e-4, "{}", total(&cylinder));
+        assert!((total(&saddle) - tau).abs() < 1e-4, "{}", total(&saddle));
+    }
+
+    #[test]
+    fn cotangent_mean_curvature_on_sphere() {
+        let radius = 2.0;
+        let mut surface = surfaces::sphere(radius, 32);
+
+        // H pelo ajuste do parabolóide, com as normais do arquivo.
+        let paraboloid_h = |mesh: &HalfEdgeMesh| {
+            let nbhds = geom::compute_neighborhoods(mesh);
+            let normals = geom::compute_avg_normals(mesh);
+            let bases = geom::compute_tangent_basis(mesh, &nbhds, &normals);
+            let shape_ops = geom::compute_shape_operator(mesh, &nbhds, &bases);
+
+            geom::compute_curvatures(&shape_ops).into_iter().map(|(_, h)| h).collect::<Vec<_>>()
+        };
+
+        let mesh = HalfEdgeMesh::new(&surface.mesh).unwrap();
+        let cotangent = compute_cotangent_mean_curvatures(&mesh);
+        let paraboloid = paraboloid_h(&mesh);
+
+        for (v, &(h, _)) in cotangent.iter().enumerate() {
+            assert!((h - 1.0 / radius).abs() < 0.01 / radius, "H = {} at vertex {}", h, v);
+            assert!(paraboloid[v] > 0.0, "paraboloid H = {} at vertex {}", paraboloid[v], v);
+        }
+
+        // Invertendo a orientação das faces e as normais, as duas trocam de
+        // sinal juntas.
+        for t in surface.mesh.triangles.iter_mut() {
+            t.swap(1, 2);
+        }
+        for n in surface.mesh.normals.iter_mut().flatten() {
+            *n = -*n;
+        }
+
+        let mesh = HalfEdgeMesh::new(&surface.mesh).unwrap();
+        let cotangent = compute_cotangent_mean_curvatures(&mesh);
+        let paraboloid = paraboloid_h(&mesh);
+
+        for (v, &(h, _)) in cotangent.iter().enumerate() {
+            assert!((h + 1.0 / radius).abs() < 0.01 / radius, "H = {} at vertex {}", h, v);
+            assert!(paraboloid[v] < 0.0, "paraboloid H = {} at vertex {}", paraboloid[v], v);
+        }
+    }
+}
@@ -0,0 +1,530 @@
+//! # Precisão dupla
+//!
+//! Versões em `f64` dos cálculos de [`crate::geom`] e [`crate::discrete`]
+//! usados pelo [`crate::pipeline`] (ver [`crate::geom::Precision`]).
+//!
+//! Em malhas muito pequenas ou muito refinadas, várias etapas perdem
+//! dígitos em `f32`, e não só o ajuste do parabolóide: as equações normais
+//! elevam ao quadrado o número de condição, o defeito angular
+//! $2\pi - \sum_j \theta_j$ é a diferença entre dois números quase iguais,
+//! e na fórmula das cotangentes o vetor curvatura média é uma soma de
+//! termos muito maiores do que ele. Aqui as posições são convertidas para
+//! `f64` antes de qualquer conta, inclusive das arestas, e todas as etapas
+//! (normais, bases tangentes, áreas, ajuste, curvaturas e estimadores
+//! discretos) são feitas em `f64`.
+//!
+//! Cada função faz as mesmas contas da sua versão em `f32`, com o mesmo
+//! nome, e a documentação dos métodos está lá. Só o resultado final, em
+//! [`crate::pipeline::VertexAttributes`], volta para `f32`: os valores em si
+//! cabem com folga em `f32`, o problema era o que se perdia no caminho.
+
+use std::{collections::BTreeSet, f64::consts::PI};
+
+use ultraviolet::{DMat2, DMat3, DVec2, DVec3, Vec3};
+
+use crate::{
+    geom::{self, FitWeighting, NormalWeighting, VertexStatus},
+    halfedge::HalfEdgeMesh,
+    par,
+};
+
+/// Converte um vetor para `f64`.
+pub fn to_f64(v: Vec3) -> DVec3 {
+    DVec3::new(v.x as f64, v.y as f64, v.z as f64)
+}
+
+/// Converte um vetor de volta para `f32`.
+pub fn to_f32(v: DVec3) -> Vec3 {
+    Vec3::new(v.x as f32, v.y as f32, v.z as f32)
+}
+
+fn position(mesh: &HalfEdgeMesh, v: usize) -> DVec3 {
+    to_f64(mesh.position(v))
+}
+
+fn face_positions(mesh: &HalfEdgeMesh, f: usize) -> [DVec3; 3] {
+    mesh.face_positions(f).map(to_f64)
+}
+
+/// Ver [`geom::compute_avg_normals`].
+pub fn compute_avg_normals(mesh: &HalfEdgeMesh) -> Vec<DVec3> {
+    if !mesh.has_normals() {
+        return compute_weighted_normals(mesh, NormalWeighting::Area);
+    }
+
+    par::map(mesh.n_vertices(), |v| {
+        let (sum, count) = mesh
+            .corners(v)
+            .fold((DVec3::zero(), 0usize), |(sum, count), c| {
+                (sum + to_f64(mesh.corner_normal(c).unwrap()), count + 1)
+            });
+
+        (sum / count as f64).normalized()
+    })
+}
+
+/// Ver [`geom::compute_weighted_normals`].
+pub fn compute_weighted_normals(mesh: &HalfEdgeMesh, weighting: NormalWeighting) -> Vec<DVec3> {
+    par::map(mesh.n_vertices(), |v| {
+        let mut sum = DVec3::zero();
+
+        for c in mesh.corners(v) {
+            let p = position(mesh, v);
+            let e1 = position(mesh, mesh.target(c)) - p;
+            let e2 = position(mesh, mesh.opposite_vertex(c)) - p;
+
+            let cross = e1.cross(e2);
+
+            let contribution = match weighting {
+                NormalWeighting::Uniform => cross.normalized(),
+                NormalWeighting::Area => cross,
+                NormalWeighting::Angle => {
+                    let angle = e1.normalized().dot(e2.normalized()).clamp(-1.0, 1.0).acos();
+
+                    angle * cross.normalized()
+                }
+                NormalWeighting::Max => cross / (e1.mag_sq() * e2.mag_sq()),
+            };
+
+            if contribution.x.is_finite() && contribution.y.is_finite() && contribution.z.is_finite() {
+                sum += contribution;
+            }
+        }
+
+        sum.normalized()
+    })
+}
+
+/// Ver [`geom::compute_pca_normals`].
+pub fn compute_pca_normals(mesh: &HalfEdgeMesh, nbhds: &[BTreeSet<u32>]) -> Vec<DVec3> {
+    let face_normals = compute_weighted_normals(mesh, NormalWeighting::Area);
+
+    par::map(mesh.n_vertices(), |i| {
+        let points = std::iter::once(i)
+            .chain(nbhds[i].iter().map(|&nb| nb as usize))
+            .map(|idx| position(mesh, idx))
+            .collect::<Vec<_>>();
+
+        let centroid = points.iter().fold(DVec3::zero(), |acc, p| acc + *p) / points.len() as f64;
+
+        let mut cov = DMat3::new(DVec3::zero(), DVec3::zero(), DVec3::zero());
+        for p in points.iter() {
+            let d = *p - centroid;
+            cov += DMat3::new(d * d.x, d * d.y, d * d.z);
+        }
+
+        let n = smallest_eigenvector(&cov);
+
+        if n.dot(face_normals[i]) < 0.0 { -n } else { n }
+    })
+}
+
+/// Ver o `smallest_eigenvector` de [`crate::geom`].
+fn smallest_eigenvector(m: &DMat3) -> DVec3 {
+    let [c0, c1, c2] = m.cols;
+    let (a00, a11, a22) = (c0.x, c1.y, c2.z);
+    let (a01, a02, a12) = (c1.x, c2.x, c2.y);
+
+    let off = a01 * a01 + a02 * a02 + a12 * a12;
+    let q = (a00 + a11 + a22) / 3.0;
+
+    let lambda = if off == 0.0 {
+        a00.min(a11).min(a22)
+    } else {
+        let p2 = (a00 - q).powi(2) + (a11 - q).powi(2) + (a22 - q).powi(2) + 2.0 * off;
+        let p = (p2 / 6.0).sqrt();
+
+        let b = (1.0 / p) * shift_diagonal(m, -q);
+        let r = (0.5 * b.determinant()).clamp(-1.0, 1.0);
+        let phi = r.acos() / 3.0;
+
+        q + 2.0 * p * (phi + 2.0 * PI / 3.0).cos()
+    };
+
+    let [r0, r1, r2] = shift_diagonal(m, -lambda).cols;
+
+    [r0.cross(r1), r0.cross(r2), r1.cross(r2)]
+        .into_iter()
+        .max_by(|a, b| a.mag_sq().total_cmp(&b.mag_sq()))
+        .unwrap()
+        .normalized()
+}
+
+fn shift_diagonal(m: &DMat3, s: f64) -> DMat3 {
+    let mut ret = *m;
+
+    ret.cols[0].x += s;
+    ret.cols[1].y += s;
+    ret.cols[2].z += s;
+
+    ret
+}
+
+/// Ver [`geom::compute_tangent_basis`].
+pub fn compute_tangent_basis(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    normals: &[DVec3],
+) -> Vec<DMat3> {
+    par::map(mesh.n_vertices(), |i| {
+        let p = position(mesh, i);
+        let n = normals[i];
+
+        let project = |a_tilde: DVec3| {
+            let a = a_tilde - n * a_tilde.dot(n);
+
+            if a.mag_sq() > 1e-12 * a_tilde.mag_sq() { Some(a.normalized()) } else { None }
+        };
+
+        let a = nbhds[i]
+            .iter()
+            .next()
+            .and_then(|&nb| project(position(mesh, nb as usize) - p))
+            .unwrap_or_else(|| {
+                let axis = if n.x.abs() <= n.y.abs() && n.x.abs() <= n.z.abs() {
+                    DVec3::unit_x()
+                } else if n.y.abs() <= n.z.abs() {
+                    DVec3::unit_y()
+                } else {
+                    DVec3::unit_z()
+                };
+
+                project(axis).unwrap_or(axis)
+            });
+
+        let b = n.cross(a).normalized();
+
+        DMat3::new(a, b, n)
+    })
+}
+
+/// Ver [`geom::compute_vertex_areas`].
+pub fn compute_vertex_areas(mesh: &HalfEdgeMesh) -> Vec<f64> {
+    par::map(mesh.n_vertices(), |v| {
+        mesh.corners(v)
+            .map(|c| {
+                let [v1, v2, v3] = face_positions(mesh, mesh.face(c));
+
+                0.5 * (v2 - v1).cross(v3 - v1).mag() / 3.0
+            })
+            .fold(0.0, |acc, a| acc + a)
+    })
+}
+
+/// O peso do vizinho `nb`, a uma diferença `d` do vértice, no ajuste.
+fn fit_weight(weighting: FitWeighting, areas: &[f64], nb: usize, d: DVec3) -> f64 {
+    match weighting {
+        FitWeighting::Uniform => 1.0,
+        FitWeighting::InverseDistance => 1.0 / d.mag_sq(),
+        FitWeighting::Area => areas[nb],
+    }
+}
+
+/// Ver [`geom::compute_shape_operator_checked`].
+#[allow(non_snake_case)]
+pub fn compute_shape_operator(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    tangent_bases: &[DMat3],
+    weighting: FitWeighting,
+) -> Vec<Result<DMat2, VertexStatus>> {
+    let areas = match weighting {
+        FitWeighting::Area => compute_vertex_areas(mesh),
+        _ => Vec::new(),
+    };
+
+    par::map(mesh.n_vertices(), |i| {
+        let nbhds = &nbhds[i];
+
+        if nbhds.is_empty() {
+            return Err(VertexStatus::Isolated);
+        }
+
+        if nbhds.len() < 3 {
+            return Err(VertexStatus::TooFewNeighbours);
+        }
+
+        let v = position(mesh, i);
+        let tps_basis_t = tangent_bases[i].transposed();
+        let n = tangent_bases[i].cols[2];
+
+        let mut UtU = DMat3::new(DVec3::zero(), DVec3::zero(), DVec3::zero());
+        let mut UtF = DVec3::zero();
+
+        for &nb_idx in nbhds.iter() {
+            let nb_idx = nb_idx as usize;
+
+            let d = position(mesh, nb_idx) - v;
+
+            let nb_local = tps_basis_t * d;
+            let nb_h = n.dot(d);
+
+            let w = fit_weight(weighting, &areas, nb_idx, d);
+
+            let u = DVec3::new(
+                0.5 * nb_local.x * nb_local.x,
+                nb_local.x * nb_local.y,
+                0.5 * nb_local.y * nb_local.y,
+            );
+
+            UtU += DMat3::new(u * (w * u.x), u * (w * u.y), u * (w * u.z));
+            UtF += u * (w * nb_h);
+        }
+
+        // O mesmo teste de `compute_shape_operator_checked`. Em `f64` ele
+        // só rejeita vértices realmente degenerados.
+        let scale = (UtU.cols[0].x + UtU.cols[1].y + UtU.cols[2].z) / 3.0;
+        if UtU.determinant().abs() <= 1e-6 * scale.powi(3) {
+            return Err(VertexStatus::SingularFit);
+        }
+
+        let X = UtU.inversed() * UtF;
+
+        if !(X.x.is_finite() && X.y.is_finite() && X.z.is_finite()) {
+            return Err(VertexStatus::SingularFit);
+        }
+
+        Ok(-1.0 * DMat2::new(
+            DVec2::new(X.x, X.y),
+            DVec2::new(X.y, X.z),
+        ))
+    })
+}
+
+/// Ver [`geom::compute_fit_residuals`].
+pub fn compute_fit_residuals(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    tangent_bases: &[DMat3],
+    shape_ops: &[DMat2],
+    weighting: FitWeighting,
+) -> Vec<f64> {
+    let areas = match weighting {
+        FitWeighting::Area => compute_vertex_areas(mesh),
+        _ => Vec::new(),
+    };
+
+    par::map(mesh.n_vertices(), |i| {
+        let shape = &shape_ops[i];
+        let (a, b, c) = (-shape.cols[0].x, -shape.cols[0].y, -shape.cols[1].y);
+
+        if !(a.is_finite() && b.is_finite() && c.is_finite()) {
+            return f64::NAN;
+        }
+
+        let v = position(mesh, i);
+        let tps_basis_t = tangent_bases[i].transposed();
+        let n = tangent_bases[i].cols[2];
+
+        let mut sum = 0.0;
+        let mut total_weight = 0.0;
+
+        for &nb_idx in &nbhds[i] {
+            let nb_idx = nb_idx as usize;
+            let d = position(mesh, nb_idx) - v;
+            let local = tps_basis_t * d;
+
+            let w = fit_weight(weighting, &areas, nb_idx, d);
+
+            let fitted = 0.5 * a * local.x * local.x + b * local.x * local.y + 0.5 * c * local.y * local.y;
+            let err = n.dot(d) - fitted;
+
+            sum += w * err * err;
+            total_weight += w;
+        }
+
+        (sum / total_weight).sqrt()
+    })
+}
+
+/// Ver [`geom::compute_curvatures`].
+pub fn compute_curvatures(shape_ops: &[DMat2]) -> Vec<(f64, f64)> {
+    par::map(shape_ops.len(), |i| {
+        let shape = &shape_ops[i];
+
+        (shape.determinant(), 0.5 * (shape.cols[0].x + shape.cols[1].y))
+    })
+}
+
+/// Curvaturas e direções principais de um vértice, em `f64` (ver
+/// [`geom::PrincipalCurvatures`]).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrincipalCurvatures {
+    pub k1: f64,
+    pub k2: f64,
+    pub d1: DVec3,
+    pub d2: DVec3,
+}
+
+impl PrincipalCurvatures {
+    /// Converte para `f32`.
+    pub fn to_f32(&self) -> geom::PrincipalCurvatures {
+        geom::PrincipalCurvatures {
+            k1: self.k1 as f32,
+            k2: self.k2 as f32,
+            d1: to_f32(self.d1),
+            d2: to_f32(self.d2),
+        }
+    }
+}
+
+/// Ver [`geom::compute_principal_directions`].
+pub fn compute_principal_directions(
+    shape_ops: &[DMat2],
+    tangent_bases: &[DMat3],
+) -> Vec<PrincipalCurvatures> {
+    par::map(shape_ops.len(), |i| {
+        let (shape, basis) = (&shape_ops[i], &tangent_bases[i]);
+
+        let a = shape.cols[0].x;
+        let b = shape.cols[0].y;
+        let c = shape.cols[1].y;
+
+        let half_tr = 0.5 * (a + c);
+        let disc = (half_tr * half_tr - shape.determinant()).max(0.0).sqrt();
+
+        let theta = 0.5 * (2.0 * b).atan2(a - c);
+        let (sin, cos) = theta.sin_cos();
+
+        let to_world = |x: f64, y: f64| basis.cols[0] * x + basis.cols[1] * y;
+
+        PrincipalCurvatures {
+            k1: half_tr - disc,
+            k2: half_tr + disc,
+            d1: to_world(-sin, cos),
+            d2: to_world(cos, sin),
+        }
+    })
+}
+
+/// Cotangente do ângulo entre `u` e `v`.
+fn cot(u: DVec3, v: DVec3) -> f64 {
+    u.dot(v) / u.cross(v).mag()
+}
+
+/// Ver [`crate::discrete::compute_corner_angles`].
+pub fn compute_corner_angles(mesh: &HalfEdgeMesh) -> Vec<[f64; 3]> {
+    par::map(mesh.n_faces(), |f| {
+        let p = face_positions(mesh, f);
+
+        [0, 1, 2].map(|k| {
+            let u = p[(k + 1) % 3] - p[k];
+            let v = p[(k + 2) % 3] - p[k];
+
+            u.normalized().dot(v.normalized()).clamp(-1.0, 1.0).acos()
+        })
+    })
+}
+
+/// Ver [`crate::discrete::compute_mixed_areas`].
+pub fn compute_mixed_areas(mesh: &HalfEdgeMesh) -> Vec<f64> {
+    let angles = compute_corner_angles(mesh);
+
+    let corner_area = |c: usize| {
+        let f = mesh.face(c);
+        let p = face_positions(mesh, f);
+
+        let area = 0.5 * (p[1] - p[0]).cross(p[2] - p[0]).mag();
+        let obtuse = angles[f].iter().position(|&a| a > 0.5 * PI);
+
+        let k = c % 3;
+        let (q, r) = ((k + 1) % 3, (k + 2) % 3);
+
+        match obtuse {
+            None => {
+                let cot_q = cot(p[k] - p[q], p[r] - p[q]);
+                let cot_r = cot(p[k] - p[r], p[q] - p[r]);
+
+                0.125 * ((p[r] - p[k]).mag_sq() * cot_q + (p[q] - p[k]).mag_sq() * cot_r)
+            }
+            Some(o) if o == k => 0.5 * area,
+            Some(_) => 0.25 * area,
+        }
+    };
+
+    par::map(mesh.n_vertices(), |v| mesh.corners(v).map(corner_area).fold(0.0, |acc, a| acc + a))
+}
+
+/// Ver [`crate::discrete::compute_angle_defect_curvatures`].
+pub fn compute_angle_defect_curvatures(mesh: &HalfEdgeMesh) -> Vec<f64> {
+    let angles = compute_corner_angles(mesh);
+    let areas = compute_mixed_areas(mesh);
+
+    par::map(mesh.n_vertices(), |v| {
+        let defect = if mesh.is_isolated(v) {
+            0.0
+        } else if mesh.is_boundary_vertex(v) {
+            f64::NAN
+        } else {
+            let sum = mesh
+                .corners(v)
+                .map(|c| angles[mesh.face(c)][c % 3])
+                .fold(0.0, |acc, a| acc + a);
+
+            2.0 * PI - sum
+        };
+
+        defect / areas[v]
+    })
+}
+
+/// Ver [`crate::discrete::compute_cotangent_mean_curvatures`].
+pub fn compute_cotangent_mean_curvatures(mesh: &HalfEdgeMesh) -> Vec<(f64, DVec3)> {
+    let areas = compute_mixed_areas(mesh);
+
+    par::map(mesh.n_vertices(), |i| {
+        if mesh.is_boundary_vertex(i) {
+            return (f64::NAN, DVec3::broadcast(f64::NAN));
+        }
+
+        let xi = position(mesh, i);
+        let mut sum = DVec3::zero();
+        let mut orientation = DVec3::zero();
+
+        for c in mesh.corners(i) {
+            let xj = position(mesh, mesh.target(c));
+            let xk = position(mesh, mesh.opposite_vertex(c));
+
+            sum += cot(xi - xk, xj - xk) * (xi - xj);
+            sum += cot(xk - xj, xi - xj) * (xi - xk);
+
+            // A orientação das faces dá o sinal de H.
+            let p = face_positions(mesh, mesh.face(c));
+            orientation += (p[1] - p[0]).cross(p[2] - p[0]);
+        }
+
+        let hn = sum / (4.0 * areas[i]);
+
+        (hn.mag().copysign(hn.dot(orientation)), hn)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        geom::Precision,
+        pipeline::{PipelineOptions, VertexAttributes},
+        surfaces,
+    };
+
+    #[test]
+    fn tiny_sphere() {
+        // Com raio 1e-3, os termos de U'U são da ordem de 1e-16, e em `f32`
+        // nenhum ajuste passa no teste de singularidade.
+        let radius = 1e-3;
+        let surface = surfaces::sphere(radius, 64);
+
+        let single = VertexAttributes::compute(&surface.mesh, &PipelineOptions::default()).unwrap();
+        assert_eq!(single.n_degenerate(), single.n_vertices());
+
+        let opts = PipelineOptions { precision: Precision::Double, ..Default::default() };
+        let double = VertexAttributes::compute(&surface.mesh, &opts).unwrap();
+        assert_eq!(double.n_degenerate(), 0);
+
+        let (k, h) = (1.0 / (radius * radius), 1.0 / radius);
+        for i in 0..double.n_vertices() {
+            assert!((double.gaussian[i] - k).abs() < 0.01 * k, "K = {} at vertex {}", double.gaussian[i], i);
+            assert!((double.mean[i] - h).abs() < 0.01 * h, "H = {} at vertex {}", double.mean[i], i);
+        }
+    }
+}
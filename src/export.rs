@@ -0,0 +1,406 @@
+//! # Exportação
+//!
+//! Escreve a malha junto das grandezas calculadas em cada vértice
+//! (curvaturas gaussiana, média e principais, direções principais e
+//! normais médias) em
+//! formatos que outras ferramentas entendem. Junto vai a situação de cada
+//! vértice ([`crate::geom::VertexStatus`]) e se ele está no bordo, para que
+//! vértices degenerados ou do bordo possam ser filtrados:
+//!
+//! - PLY, com as grandezas como propriedades extras dos vértices;
+//! - CSV, uma linha por vértice;
+//! - VTK legado (`.vtk`) e VTK XML (`.vtu`), que abrem no ParaView.
+//!
+//! Todos os formatos são escritos em ASCII.
+//!
+//! Valores que não são finitos (as curvaturas dos vértices degenerados, ou
+//! dos vértices do bordo com [`crate::pipeline::BoundaryHandling::Exclude`])
+//! são escritos como [`MISSING_VALUE`]: o leitor de VTK legado do ParaView
+//! e vários leitores de PLY não aceitam `NaN` nem `inf`. Esses vértices são
+//! os que têm `status` diferente de 0 e, com os estimadores discretos, os
+//! que têm `boundary` igual a 1; eles devem ser filtrados por essas duas
+//! colunas (no ParaView, com um *Threshold*).
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use ultraviolet::Vec3;
+
+use crate::{mesh::Mesh, pipeline::VertexAttributes};
+
+/// Valor escrito no lugar de `NaN` e de infinitos.
+pub const MISSING_VALUE: f32 = 0.0;
+
+/// Formatos de arquivo suportados.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Ply,
+    Csv,
+    Vtk,
+    Vtu,
+}
+
+impl Format {
+    /// Deduz o formato a partir da extensão do arquivo.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        match ext.as_str() {
+            "ply" => Some(Format::Ply),
+            "csv" => Some(Format::Csv),
+            "vtk" => Some(Format::Vtk),
+            "vtu" => Some(Format::Vtu),
+            _ => None,
+        }
+    }
+}
+
+/// Escreve a malha e suas grandezas no arquivo `path`, no formato `format`.
+pub fn export(
+    path: &Path,
+    format: Format,
+    mesh: &Mesh,
+    attrs: &VertexAttributes,
+) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+
+    match format {
+        Format::Ply => write_ply(&mut w, mesh, attrs)?,
+        Format::Csv => write_csv(&mut w, attrs)?,
+        Format::Vtk => write_vtk(&mut w, mesh, attrs)?,
+        Format::Vtu => write_vtu(&mut w, mesh, attrs)?,
+    }
+
+    w.flush()
+}
+
+/// Escreve a malha no formato PLY.
+///
+/// As curvaturas viram as propriedades `gaussian_curvature`,
+/// `mean_curvature`, `k1` e `k2` de cada vértice, as direções principais
+/// as propriedades `d1x`, ..., `d2z`, e as normais médias as propriedades
+/// padrão `nx`, `ny` e `nz`. A propriedade `status` contém o código de
+/// [`crate::geom::VertexStatus`], e `boundary` é 1 nos vértices do bordo.
+pub fn write_ply<W: Write>(w: &mut W, mesh: &Mesh, attrs: &VertexAttributes) -> io::Result<()> {
+    let n_faces = mesh.n_faces();
+
+    writeln!(w, "ply")?;
+    writeln!(w, "format ascii 1.0")?;
+    writeln!(w, "comment generated by gauss")?;
+    writeln!(w, "element vertex {}", attrs.n_vertices())?;
+    for prop in [
+        "x", "y", "z",
+        "nx", "ny", "nz",
+        "gaussian_curvature", "mean_curvature", "k1", "k2",
+        "d1x", "d1y", "d1z",
+        "d2x", "d2y", "d2z",
+    ] {
+        writeln!(w, "property float {}", prop)?;
+    }
+    writeln!(w, "property uchar status")?;
+    writeln!(w, "property uchar boundary")?;
+    writeln!(w, "element face {}", n_faces)?;
+    writeln!(w, "property list uchar int vertex_indices")?;
+    writeln!(w, "end_header")?;
+
+    for i in 0..attrs.n_vertices() {
+        let p = finite_vec(attrs.positions[i]);
+        let n = finite_vec(attrs.normals[i]);
+
+        let d1 = finite_vec(attrs.d1[i]);
+        let d2 = finite_vec(attrs.d2[i]);
+
+        writeln!(
+            w,
+            "{} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+            p.x, p.y, p.z,
+            n.x, n.y, n.z,
+            finite(attrs.gaussian[i]), finite(attrs.mean[i]), finite(attrs.k1[i]), finite(attrs.k2[i]),
+            d1.x, d1.y, d1.z,
+            d2.x, d2.y, d2.z,
+            attrs.status[i].code(),
+            attrs.boundary[i] as u8,
+        )?;
+    }
+
+    for idxs in &mesh.triangles {
+        writeln!(w, "3 {} {} {}", idxs[0], idxs[1], idxs[2])?;
+    }
+
+    Ok(())
+}
+
+/// Escreve as grandezas como uma tabela CSV, com uma linha por vértice.
+///
+/// A coluna `status` contém o nome de [`crate::geom::VertexStatus`], e
+/// `boundary` é 1 nos vértices do bordo.
+pub fn write_csv<W: Write>(w: &mut W, attrs: &VertexAttributes) -> io::Result<()> {
+    writeln!(w, "vertex,x,y,z,nx,ny,nz,gaussian_curvature,mean_curvature,k1,k2,d1x,d1y,d1z,d2x,d2y,d2z,status,boundary")?;
+
+    for i in 0..attrs.n_vertices() {
+        let p = finite_vec(attrs.positions[i]);
+        let n = finite_vec(attrs.normals[i]);
+
+        let d1 = finite_vec(attrs.d1[i]);
+        let d2 = finite_vec(attrs.d2[i]);
+
+        writeln!(
+            w,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            i,
+            p.x, p.y, p.z,
+            n.x, n.y, n.z,
+            finite(attrs.gaussian[i]), finite(attrs.mean[i]), finite(attrs.k1[i]), finite(attrs.k2[i]),
+            d1.x, d1.y, d1.z,
+            d2.x, d2.y, d2.z,
+            attrs.status[i],
+            attrs.boundary[i] as u8,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Escreve a malha no formato VTK legado (`POLYDATA`).
+pub fn write_vtk<W: Write>(w: &mut W, mesh: &Mesh, attrs: &VertexAttributes) -> io::Result<()> {
+    let n_faces = mesh.n_faces();
+
+    writeln!(w, "# vtk DataFile Version 3.0")?;
+    writeln!(w, "generated by gauss")?;
+    writeln!(w, "ASCII")?;
+    writeln!(w, "DATASET POLYDATA")?;
+
+    writeln!(w, "POINTS {} float", attrs.n_vertices())?;
+    for &p in &attrs.positions {
+        let p = finite_vec(p);
+        writeln!(w, "{} {} {}", p.x, p.y, p.z)?;
+    }
+
+    writeln!(w, "POLYGONS {} {}", n_faces, 4 * n_faces)?;
+    for idxs in &mesh.triangles {
+        writeln!(w, "3 {} {} {}", idxs[0], idxs[1], idxs[2])?;
+    }
+
+    writeln!(w, "POINT_DATA {}", attrs.n_vertices())?;
+    for (name, values) in scalar_fields(attrs) {
+        writeln!(w, "SCALARS {} float 1", name)?;
+        writeln!(w, "LOOKUP_TABLE default")?;
+        for &v in values {
+            writeln!(w, "{}", finite(v))?;
+        }
+    }
+
+    writeln!(w, "SCALARS status unsigned_char 1")?;
+    writeln!(w, "LOOKUP_TABLE default")?;
+    for status in &attrs.status {
+        writeln!(w, "{}", status.code())?;
+    }
+
+    writeln!(w, "SCALARS boundary unsigned_char 1")?;
+    writeln!(w, "LOOKUP_TABLE default")?;
+    for &boundary in &attrs.boundary {
+        writeln!(w, "{}", boundary as u8)?;
+    }
+
+    for (name, vectors) in vector_fields(attrs) {
+        writeln!(w, "VECTORS {} float", name)?;
+        for &v in vectors {
+            let v = finite_vec(v);
+            writeln!(w, "{} {} {}", v.x, v.y, v.z)?;
+        }
+    }
+
+    writeln!(w, "NORMALS normals float")?;
+    for &n in &attrs.normals {
+        let n = finite_vec(n);
+        writeln!(w, "{} {} {}", n.x, n.y, n.z)?;
+    }
+
+    Ok(())
+}
+
+/// Escreve a malha no formato VTK XML (`UnstructuredGrid`, `.vtu`).
+pub fn write_vtu<W: Write>(w: &mut W, mesh: &Mesh, attrs: &VertexAttributes) -> io::Result<()> {
+    let n_faces = mesh.n_faces();
+
+    writeln!(w, r#"<?xml version="1.0"?>"#)?;
+    writeln!(w, r#"<VTKFile type="UnstructuredGrid" version="0.1" byte_order="LittleEndian">"#)?;
+    writeln!(w, "  <UnstructuredGrid>")?;
+    writeln!(w, r#"    <Piece NumberOfPoints="{}" NumberOfCells="{}">"#, attrs.n_vertices(), n_faces)?;
+
+    writeln!(w, r#"      <PointData Scalars="gaussian_curvature" Normals="normals">"#)?;
+    for (name, values) in scalar_fields(attrs) {
+        writeln!(w, r#"        <DataArray type="Float32" Name="{}" format="ascii">"#, name)?;
+        for &v in values {
+            writeln!(w, "          {}", finite(v))?;
+        }
+        writeln!(w, "        </DataArray>")?;
+    }
+    writeln!(w, r#"        <DataArray type="UInt8" Name="status" format="ascii">"#)?;
+    for status in &attrs.status {
+        writeln!(w, "          {}", status.code())?;
+    }
+    writeln!(w, "        </DataArray>")?;
+    writeln!(w, r#"        <DataArray type="UInt8" Name="boundary" format="ascii">"#)?;
+    for &boundary in &attrs.boundary {
+        writeln!(w, "          {}", boundary as u8)?;
+    }
+    writeln!(w, "        </DataArray>")?;
+    for (name, vectors) in vector_fields(attrs) {
+        writeln!(w, r#"        <DataArray type="Float32" Name="{}" NumberOfComponents="3" format="ascii">"#, name)?;
+        for &v in vectors {
+            let v = finite_vec(v);
+            writeln!(w, "          {} {} {}", v.x, v.y, v.z)?;
+        }
+        writeln!(w, "        </DataArray>")?;
+    }
+    writeln!(w, r#"        <DataArray type="Float32" Name="normals" NumberOfComponents="3" format="ascii">"#)?;
+    for &n in &attrs.normals {
+        let n = finite_vec(n);
+        writeln!(w, "          {} {} {}", n.x, n.y, n.z)?;
+    }
+    writeln!(w, "        </DataArray>")?;
+    writeln!(w, "      </PointData>")?;
+
+    writeln!(w, "      <Points>")?;
+    writeln!(w, r#"        <DataArray type="Float32" NumberOfComponents="3" format="ascii">"#)?;
+    for &p in &attrs.positions {
+        let p = finite_vec(p);
+        writeln!(w, "          {} {} {}", p.x, p.y, p.z)?;
+    }
+    writeln!(w, "        </DataArray>")?;
+    writeln!(w, "      </Points>")?;
+
+    writeln!(w, "      <Cells>")?;
+    writeln!(w, r#"        <DataArray type="Int32" Name="connectivity" format="ascii">"#)?;
+    for idxs in &mesh.triangles {
+        writeln!(w, "          {} {} {}", idxs[0], idxs[1], idxs[2])?;
+    }
+    writeln!(w, "        </DataArray>")?;
+    writeln!(w, r#"        <DataArray type="Int32" Name="offsets" format="ascii">"#)?;
+    for i in 0..n_faces {
+        writeln!(w, "          {}", 3 * (i + 1))?;
+    }
+    writeln!(w, "        </DataArray>")?;
+    // 5 é o código do VTK_TRIANGLE.
+    writeln!(w, r#"        <DataArray type="UInt8" Name="types" format="ascii">"#)?;
+    for _ in 0..n_faces {
+        writeln!(w, "          5")?;
+    }
+    writeln!(w, "        </DataArray>")?;
+    writeln!(w, "      </Cells>")?;
+
+    writeln!(w, "    </Piece>")?;
+    writeln!(w, "  </UnstructuredGrid>")?;
+    writeln!(w, "</VTKFile>")?;
+
+    Ok(())
+}
+
+/// Os campos escalares exportados, com os nomes usados nos arquivos.
+fn scalar_fields(attrs: &VertexAttributes) -> [(&'static str, &[f32]); 4] {
+    [
+        ("gaussian_curvature", &attrs.gaussian),
+        ("mean_curvature", &attrs.mean),
+        ("k1", &attrs.k1),
+        ("k2", &attrs.k2),
+    ]
+}
+
+/// Os campos vetoriais exportados (além das normais).
+fn vector_fields(attrs: &VertexAttributes) -> [(&'static str, &[Vec3]); 2] {
+    [
+        ("d1", &attrs.d1),
+        ("d2", &attrs.d2),
+    ]
+}
+
+/// `x`, ou [`MISSING_VALUE`] se `x` não é finito.
+fn finite(x: f32) -> f32 {
+    if x.is_finite() { x } else { MISSING_VALUE }
+}
+
+/// Aplica [`finite`] a cada coordenada de `v`.
+fn finite_vec(v: Vec3) -> Vec3 {
+    Vec3::new(finite(v.x), finite(v.y), finite(v.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{BoundaryHandling, PipelineOptions};
+
+    /// Um hexágono dividido em seis triângulos em volta do centro, um pouco
+    /// levantado: só o centro (o vértice 0) não está no bordo.
+    fn fan() -> (Mesh, VertexAttributes) {
+        let mut positions = vec![Vec3::new(0.0, 0.0, 0.1)];
+        positions.extend((0..6).map(|k| {
+            let angle = k as f32 * std::f32::consts::PI / 3.0;
+
+            Vec3::new(angle.cos(), angle.sin(), 0.0)
+        }));
+        let triangles = (0..6).map(|k| [0, k + 1, (k + 1) % 6 + 1]).collect();
+        let mesh = Mesh::new(positions, triangles);
+
+        let opts = PipelineOptions { boundary: BoundaryHandling::Exclude, ..Default::default() };
+        let attrs = VertexAttributes::compute(&mesh, &opts).unwrap();
+
+        (mesh, attrs)
+    }
+
+    fn to_string(write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+        let mut buffer = Vec::new();
+        write(&mut buffer).unwrap();
+
+        String::from_utf8(buffer).unwrap()
+    }
+
+    /// As linhas de um bloco `SCALARS` do VTK legado.
+    fn vtk_scalars<'a>(text: &'a str, name: &str) -> Vec<&'a str> {
+        let header = format!("SCALARS {} ", name);
+        let mut lines = text.lines().skip_while(|l| !l.starts_with(&header));
+
+        lines.next().unwrap();
+        assert_eq!(lines.next(), Some("LOOKUP_TABLE default"));
+
+        lines.take(7).collect()
+    }
+
+    #[test]
+    fn boundary_values_are_written_as_missing() {
+        let (mesh, attrs) = fan();
+
+        assert_eq!(attrs.boundary, [vec![false], vec![true; 6]].concat());
+        assert!(attrs.gaussian[0].is_finite());
+        assert!(attrs.gaussian[1..].iter().all(|k| k.is_nan()));
+
+        let vtk = to_string(|w| write_vtk(w, &mesh, &attrs));
+
+        let gaussian = vtk_scalars(&vtk, "gaussian_curvature");
+        assert_eq!(gaussian[0], attrs.gaussian[0].to_string());
+        assert!(gaussian[1..].iter().all(|&k| k == MISSING_VALUE.to_string()));
+        assert_eq!(vtk_scalars(&vtk, "status"), ["0", "4", "4", "4", "4", "4", "4"]);
+        assert_eq!(vtk_scalars(&vtk, "boundary"), ["0", "1", "1", "1", "1", "1", "1"]);
+
+        // A linha do primeiro vértice do bordo no PLY: posição, normal, as
+        // quatro curvaturas e as duas direções, e então status e bordo.
+        let ply = to_string(|w| write_ply(w, &mesh, &attrs));
+        let row = ply.lines().skip_while(|&l| l != "end_header").nth(2).unwrap();
+        let fields = row.split(' ').collect::<Vec<_>>();
+
+        assert_eq!(fields.len(), 18);
+        assert_eq!(fields[..3], ["1", "0", "0"]);
+        assert!(fields[6..16].iter().all(|&f| f == "0"));
+        assert_eq!(fields[16..], ["4", "1"]);
+
+        let csv = to_string(|w| write_csv(w, &attrs));
+        let vtu = to_string(|w| write_vtu(w, &mesh, &attrs));
+
+        for text in [&vtk, &ply, &csv, &vtu] {
+            assert!(!text.contains("NaN") && !text.contains("inf"));
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! # Exportação pela linha de comando
+//!
+//! Implementação do subcomando `gauss export`, que salva as curvaturas
+//! calculadas usando [`gauss::export`].
+
+use gauss::{
+    export::{self, Format},
+    mesh::Mesh,
+    pipeline::VertexAttributes,
+};
+
+use crate::cli::ExportArgs;
+
+/// Carrega a malha, calcula as curvaturas e escreve o arquivo de saída.
+///
+/// Erros são impressos em `stderr`. Retorna `false` se algo falhou.
+pub fn run(args: &ExportArgs) -> bool {
+    let format = match args.format.map(Format::from).or_else(|| Format::from_path(&args.output)) {
+        Some(format) => format,
+        None => {
+            eprintln!(
+                "could not infer export format from {}; use --format",
+                args.output.display(),
+            );
+            return false;
+        }
+    };
+
+    let models = match gauss::load_obj(&args.mesh) {
+        Ok(models) => models,
+        Err(e) => {
+            eprintln!("failed to load model {}: {}", args.mesh.display(), e);
+            return false;
+        }
+    };
+
+    let model = match models.get(args.model) {
+        Some(model) => model,
+        None => {
+            eprintln!(
+                "{} has {} model(s), there is no model {}",
+                args.mesh.display(),
+                models.len(),
+                args.model,
+            );
+            return false;
+        }
+    };
+
+    let mesh = match Mesh::try_from(&model.mesh) {
+        Ok(mesh) => mesh,
+        Err(e) => {
+            eprintln!("failed to process model {}: {}", args.mesh.display(), e);
+            return false;
+        }
+    };
+
+    let attrs = match VertexAttributes::compute(&mesh, &args.pipeline.options()) {
+        Ok(attrs) => attrs,
+        Err(e) => {
+            eprintln!("failed to process model {}: {}", args.mesh.display(), e);
+            return false;
+        }
+    };
+
+    if attrs.n_degenerate() > 0 {
+        eprintln!(
+            "warning: {} degenerate vertices in {}; their curvatures are NaN",
+            attrs.n_degenerate(),
+            args.mesh.display(),
+        );
+    }
+
+    if let Err(e) = export::export(&args.output, format, &mesh, &attrs) {
+        eprintln!("failed to write {}: {}", args.output.display(), e);
+        return false;
+    }
+
+    true
+}
@@ -15,9 +15,9 @@
 //! geometria da malha triangular original, sendo que quanto maior a resolução,
 //! maior a convergência da solução aos valores reais corretos. Provavelmente
 //! é possível melhorar a aproximação que fizemos aqui, mas requeriria mais
-//! computações (como considerar dois níveis de vizinhança para cada vértice),
-//! ou uma álgebra mais refinada (como aumentar o número de pontos usados no
-//! ajuste).
+//! computações. O ajuste usa todos os vizinhos de cada vértice, e não só
+//! três deles, e a vizinhança pode ser aumentada (dois ou mais níveis de
+//! vizinhança, ou uma bola de raio fixo; ver [`compute_neighborhoods_with`]).
 //!
 //! Para os fins do projeto atual, considero o resultado corrente satisfatório.
 //!
@@ -48,13 +48,25 @@
 //! Utilizaremos muito a informação das faces para calcular, especialmente, as
 //! vizinhanças e as normais médias, como veremos a frente.
 //!
+//! Depois de carregado, o modelo é convertido num [`crate::mesh::Mesh`],
+//! que guarda as mesmas informações sem depender do formato do arquivo.
+//! Malhas geradas na memória, ou carregadas por outras bibliotecas, também
+//! podem ser convertidas nele.
+//!
+//! Para não precisarmos percorrer a lista de faces toda vez que quisermos
+//! saber quem é vizinho de quem, as funções desse módulo recebem a malha já
+//! convertida numa [`HalfEdgeMesh`] (ver [`crate::halfedge`]), que guarda
+//! essa conectividade: os vizinhos de cada vértice em ordem, as faces em
+//! volta de cada vértice e de cada aresta, e quais arestas estão no bordo.
+//!
 //! ## Obtendo a parametrização local
 //!
 //! Para conseguir a aproximação local, tentamos *ajustar um parabolóide
 //! a cada vizinhança*. Ou seja, para cada vértice, tentamos calcular os
 //! parâmetros de um parabolóide tal que ele a) seja centrado no vértice
-//! considerado e b) contenha em sua imagem um número fixo de vizinhos
-//! desse vértice. Para facilitar os cálculos, consideramos 3 vizinhos.
+//! considerado e b) passe o mais perto possível dos vizinhos desse
+//! vértice. Usamos todos os vizinhos imediatos, e resolvemos o sistema
+//! sobredeterminado resultante por quadrados mínimos.
 //!
 //! Para encontrar tal parametrização, se $p \in \mathbb{R}^3$ é o vértice
 //! considerado, olhamos para o plano tangente em $p$, o $T_pS$. Encontraremos
@@ -74,79 +86,380 @@
 //! A ordem lógica de leitura é a seguinte:
 //!
 //! 1. [`compute_neighborhoods`]: dada a descrição da malha triangular,
-//!    encontra as vizinhanças de cada vértice.
+//!    encontra as vizinhanças de cada vértice. Opcionalmente, essas
+//!    vizinhanças são aumentadas por [`compute_neighborhoods_with`].
 //! 2. [`compute_avg_normals`]: calcula a "normal média" para cada vértice.
+//!    Alternativamente, [`compute_weighted_normals`] calcula normais a partir
+//!    da geometria das faces, com diferentes ponderações, e
+//!    [`compute_pca_normals`] a partir da vizinhança de cada vértice.
 //! 3. [`compute_tangent_basis`]: para cada vértice p, calcula uma base de TpS.
 //! 4. [`compute_shape_operator`]: calcula a matriz que representa o Shape Operator
 //!    para cada vértice.
 //! 5. [`compute_curvatures`]: a partir das matrizes dos Shape Operators, calcula
 //!    as curvaturas gaussianas e médias.
+//! 6. [`compute_principal_directions`]: alternativamente, calcula as curvaturas
+//!    principais e as direções em que elas ocorrem (os autovalores e os
+//!    autovetores de cada Shape Operator).
+//!
+//! Além disso, [`compute_vertex_areas`] calcula a área associada a cada
+//! vértice, que usamos para integrar as curvaturas sobre a malha, e
+//! [`compute_fit_residuals`] mede o quanto cada vizinhança se afasta do
+//! parabolóide ajustado.
+//!
+//! ## Vértices degenerados
+//!
+//! Nem todo vértice permite o ajuste do parabolóide: vértices soltos (que
+//! não aparecem em nenhuma face), vértices com menos de três vizinhos, ou
+//! com os vizinhos numa configuração degenerada. Em vez de entrar em pânico
+//! ou propagar infinitos silenciosamente, [`compute_shape_operator_checked`]
+//! devolve um [`VertexStatus`] para esses vértices, e quem chama decide se
+//! os ignora, os marca ou usa outro estimador.
+//!
+//! ## Precisão
+//!
+//! As equações normais $(U' U)^{-1} U' F$ elevam ao quadrado o número de
+//! condição do problema de quadrados mínimos. Em malhas muito pequenas ou
+//! muito refinadas, onde as coordenadas locais dos vizinhos são minúsculas,
+//! os termos de $U' U$ (da ordem da quarta potência do tamanho das arestas)
+//! perdem a maior parte dos dígitos significativos em `f32`.
+//! [`compute_shape_operator_f64`] faz o mesmo ajuste em `f64`, e o módulo
+//! [`crate::double`] tem as outras etapas em `f64` (ver [`Precision`]).
+//!
+//! Problemas na malha como um todo (índices inválidos, faces que não são
+//! triângulos) são detectados ao construir a malha (ver
+//! [`crate::mesh::Mesh::validate`]), e viram um [`GeomError`].
 //!
 //! TODO(edu): trocar verbatim LaTeX com unicode para renderizar no RustDoc
 
-use std::collections::{HashMap, BTreeSet};
+use std::{
+    collections::{HashMap, BTreeSet},
+    fmt,
+};
+
+use ultraviolet::{f32x8, DMat3, Mat3, Mat3x8, Vec3, Vec3x8, Mat2, Vec2};
+
+use crate::{double, halfedge::HalfEdgeMesh, par};
+
+/// Erros que impedem o cálculo sobre a malha inteira.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GeomError {
+    /// O número de coordenadas não é múltiplo de 3.
+    MalformedPositions { len: usize },
+    /// O número de índices não é múltiplo de 3, então a malha não é
+    /// composta só de triângulos.
+    NotTriangulated { indices: usize },
+    /// A face `face` referencia o vértice `index`, que não existe.
+    IndexOutOfBounds { face: usize, index: u32, vertices: usize },
+    /// O canto `corner` referencia a normal `index`, que não existe.
+    NormalIndexOutOfBounds { corner: usize, index: u32, normals: usize },
+    /// Um atributo da malha não tem um valor para cada vértice (ou canto).
+    AttributeLength { attribute: &'static str, expected: usize, found: usize },
+    /// A aresta entre os vértices `edge` é compartilhada por mais de duas
+    /// faces, então a malha não é uma variedade.
+    NonManifoldEdge { edge: [u32; 2], faces: usize },
+}
+
+impl fmt::Display for GeomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeomError::MalformedPositions { len } => write!(
+                f,
+                "mesh has {} position coordinates, which is not a multiple of 3",
+                len,
+            ),
+            GeomError::NotTriangulated { indices } => write!(
+                f,
+                "mesh is not triangulated ({} indices is not a multiple of 3)",
+                indices,
+            ),
+            GeomError::IndexOutOfBounds { face, index, vertices } => write!(
+                f,
+                "face {} references vertex {}, but the mesh has only {} vertices",
+                face, index, vertices,
+            ),
+            GeomError::NormalIndexOutOfBounds { corner, index, normals } => write!(
+                f,
+                "corner {} references normal {}, but the mesh has only {} normals",
+                corner, index, normals,
+            ),
+            GeomError::AttributeLength { attribute, expected, found } => write!(
+                f,
+                "mesh has {} {}, expected {}",
+                found, attribute, expected,
+            ),
+            GeomError::NonManifoldEdge { edge, faces } => write!(
+                f,
+                "edge between vertices {} and {} is shared by {} faces, but at most 2 are allowed",
+                edge[0], edge[1], faces,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GeomError {}
+
+/// Situação de um vértice em relação ao ajuste do parabolóide.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VertexStatus {
+    /// O ajuste foi feito normalmente.
+    Ok,
+    /// O vértice não aparece em nenhuma face.
+    Isolated,
+    /// O vértice tem menos de três vizinhos, e o ajuste (que tem três
+    /// incógnitas) fica indeterminado.
+    TooFewNeighbours,
+    /// Os vizinhos estão numa configuração degenerada (por exemplo,
+    /// alinhados), e o sistema de quadrados mínimos é singular.
+    SingularFit,
+    /// O vértice está no bordo da malha, e foi deixado de fora dos cálculos
+    /// de propósito (ver [`crate::pipeline::BoundaryHandling::Exclude`]).
+    Boundary,
+}
+
+impl VertexStatus {
+    /// Todas as situações, na ordem da declaração.
+    pub const ALL: [VertexStatus; 5] = [
+        VertexStatus::Ok,
+        VertexStatus::Isolated,
+        VertexStatus::TooFewNeighbours,
+        VertexStatus::SingularFit,
+        VertexStatus::Boundary,
+    ];
+
+    /// Nome usado na linha de comando e nos arquivos exportados.
+    pub fn name(self) -> &'static str {
+        match self {
+            VertexStatus::Ok => "ok",
+            VertexStatus::Isolated => "isolated",
+            VertexStatus::TooFewNeighbours => "too_few_neighbours",
+            VertexStatus::SingularFit => "singular_fit",
+            VertexStatus::Boundary => "boundary",
+        }
+    }
+
+    /// Código numérico, para formatos que só aceitam números.
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn is_ok(self) -> bool {
+        self == VertexStatus::Ok
+    }
 
-use ultraviolet::{Mat3, Vec3, Mat2, Vec2};
+    /// Se o ajuste falhou por causa da geometria da malha em volta do
+    /// vértice (e não porque o vértice foi excluído de propósito).
+    pub fn is_degenerate(self) -> bool {
+        matches!(
+            self,
+            VertexStatus::Isolated | VertexStatus::TooFewNeighbours | VertexStatus::SingularFit,
+        )
+    }
+}
+
+impl fmt::Display for VertexStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
 
 /// Calcula as vizinhanças imediatas de cada vértice.
 ///
-/// Para isso, vamos observar que cada face nos dá a informação de
-/// vizinhança pra tres vértices de uma vez só. Visitamos cada vértice
-/// da face, e adicionamos os vértices adjacentes a uma lista.
-pub fn compute_neighborhoods(mesh: &tobj::Mesh) -> Vec<BTreeSet<u32>> {
-    // Estrutura que armazena associações (índice do vértice, conjunto de adjacências)
-    let mut nbhds = HashMap::new();
-
-    // Como estamos trabalhando sobre malhas de triangulos, cada face
-    // tem exatamente tres vertices. Portanto o número de faces total
-    // será o número de indices / 3.
-    let n_faces = mesh.indices.len() / 3;
-
-    for i in 0..n_faces {
-        // Aqui, estamos olhando para a face de índice i. Ela é composta
-        // dos vértices v1, v2 e v3.
-
-        let v1_idx =  mesh.indices[3*i    ];
-        let v2_idx =  mesh.indices[3*i + 1];
-        let v3_idx =  mesh.indices[3*i + 2];
-
-        // Olha para o vértice v1, e adiciona v2 e v3 a sua lista de
-        // adjacências.
-        {
-            let entry = nbhds.entry(v1_idx).or_insert(BTreeSet::new());
-
-            entry.insert(v2_idx);
-            entry.insert(v3_idx);
+/// Para isso, vamos observar que, em cada face que contém um vértice, os
+/// outros dois vértices da face são vizinhos dele. Visitamos todos os
+/// cantos de cada vértice e adicionamos esses vértices a um conjunto (ver
+/// [`HalfEdgeMesh::neighborhoods`]). Cada vértice é calculado de forma
+/// independente, o que permite calculá-los em paralelo.
+///
+/// Os conjuntos não guardam a ordem dos vizinhos em volta do vértice; ela é
+/// dada por [`HalfEdgeMesh::one_ring`].
+pub fn compute_neighborhoods(mesh: &HalfEdgeMesh) -> Vec<BTreeSet<u32>> {
+    mesh.neighborhoods()
+}
+
+/// Como escolher a vizinhança usada no ajuste do parabolóide.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Neighborhood {
+    /// Os vértices a no máximo `k` arestas de distância (o "k-anel").
+    /// `Ring(1)` é a vizinhança imediata.
+    Ring(usize),
+    /// Os vértices a distância euclidiana no máximo `r`.
+    Ball(f32),
+    /// Os vértices a distância geodésica (aproximada) no máximo `r`.
+    Geodesic(f32),
+}
+
+impl Default for Neighborhood {
+    fn default() -> Self {
+        Neighborhood::Ring(1)
+    }
+}
+
+/// Calcula as vizinhanças de cada vértice segundo `kind`.
+///
+/// Vizinhanças maiores deixam o ajuste mais robusto a ruído, ao custo de
+/// localidade: o parabolóide passa a descrever uma região maior da
+/// superfície. Em malhas densas (como escaneamentos), a vizinhança imediata
+/// pode ser pequena demais, e vale a pena usar dois ou três anéis.
+///
+/// `nbhds` são as vizinhanças imediatas, calculadas por
+/// [`compute_neighborhoods`].
+///
+/// Nas bolas (euclidianas ou geodésicas), a vizinhança imediata é sempre
+/// incluída, mesmo que algum vizinho esteja fora do raio, para que o ajuste
+/// tenha pontos suficientes.
+pub fn compute_neighborhoods_with(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    kind: Neighborhood,
+) -> Vec<BTreeSet<u32>> {
+    match kind {
+        Neighborhood::Ring(k) => compute_k_ring_neighborhoods(nbhds, k),
+        Neighborhood::Ball(r) => compute_ball_neighborhoods(mesh, nbhds, r),
+        Neighborhood::Geodesic(r) => compute_geodesic_neighborhoods(mesh, nbhds, r),
+    }
+}
+
+/// Calcula o k-anel de cada vértice.
+///
+/// O k-anel é obtido por uma busca em largura a partir do vértice,
+/// andando pelas arestas da malha por no máximo `k` passos. O próprio
+/// vértice não faz parte da vizinhança.
+pub fn compute_k_ring_neighborhoods(
+    nbhds: &[BTreeSet<u32>],
+    k: usize,
+) -> Vec<BTreeSet<u32>> {
+    par::map(nbhds.len(), |i| {
+        let mut ring = BTreeSet::new();
+        let mut frontier = vec![i as u32];
+
+        for _ in 0..k {
+            let mut next = Vec::new();
+
+            for vtx in frontier {
+                for &nb in nbhds[vtx as usize].iter() {
+                    if nb as usize != i && ring.insert(nb) {
+                        next.push(nb);
+                    }
+                }
+            }
+
+            frontier = next;
         }
 
-        // Olha para o vértice v2, e adiciona v1 e v3 a sua lista de
-        // adjacências.
-        {
-            let entry = nbhds.entry(v2_idx).or_insert(BTreeSet::new());
+        ring
+    })
+}
+
+/// Calcula, para cada vértice, os vértices a distância euclidiana no
+/// máximo `r` dele.
+///
+/// Em vez de testar todos os pares de vértices, partimos do vértice e
+/// andamos pelas arestas enquanto estivermos dentro da bola. Assim, pontos
+/// que estão dentro da bola mas em outra parte da superfície (do outro
+/// lado de uma dobra fina, por exemplo) não entram na vizinhança.
+pub fn compute_ball_neighborhoods(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    r: f32,
+) -> Vec<BTreeSet<u32>> {
+    let positions = mesh.positions();
 
-            entry.insert(v1_idx);
-            entry.insert(v3_idx);
+    par::map(nbhds.len(), |i| {
+        let center = positions[i];
+
+        let mut ball = nbhds[i].clone();
+        let mut stack = ball.iter().copied().collect::<Vec<_>>();
+
+        while let Some(vtx) = stack.pop() {
+            for &nb in nbhds[vtx as usize].iter() {
+                if nb as usize != i
+                    && (positions[nb as usize] - center).mag() <= r
+                    && ball.insert(nb)
+                {
+                    stack.push(nb);
+                }
+            }
         }
 
-        // Olha para o vértice v3, e adiciona v1 e v2 a sua lista de
-        // adjacências.
-        {
-            let entry = nbhds.entry(v3_idx).or_insert(BTreeSet::new());
+        ball
+    })
+}
+
+/// Calcula, para cada vértice, os vértices a distância geodésica no
+/// máximo `r` dele.
+///
+/// Aproximamos a distância geodésica pelo caminho mais curto ao longo das
+/// arestas da malha, calculado pelo algoritmo de Dijkstra. Essa aproximação
+/// superestima a distância real, mas é boa o suficiente para escolher
+/// vizinhanças.
+pub fn compute_geodesic_neighborhoods(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    r: f32,
+) -> Vec<BTreeSet<u32>> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    let positions = mesh.positions();
+
+    par::map(nbhds.len(), |i| {
+        let mut dists = HashMap::new();
+        // f32 não implementa `Ord`, mas para distâncias não-negativas
+        // a ordem dos bits é a mesma ordem dos números.
+        let mut heap = BinaryHeap::new();
+
+        dists.insert(i as u32, 0.0f32);
+        heap.push(Reverse((0.0f32.to_bits(), i as u32)));
+
+        while let Some(Reverse((dist, vtx))) = heap.pop() {
+            let dist = f32::from_bits(dist);
+
+            if dist > dists[&vtx] {
+                continue;
+            }
 
-            entry.insert(v1_idx);
-            entry.insert(v2_idx);
+            for &nb in nbhds[vtx as usize].iter() {
+                let nb_dist = dist + (positions[nb as usize] - positions[vtx as usize]).mag();
+
+                if nb_dist <= r && dists.get(&nb).is_none_or(|&d| nb_dist < d) {
+                    dists.insert(nb, nb_dist);
+                    heap.push(Reverse((nb_dist.to_bits(), nb)));
+                }
+            }
         }
-    }
 
-    // Housekeeping só para retornar os dados numa estrutura de dados
-    // mais amigável.
-    let mut ret = vec![BTreeSet::new(); mesh.positions.len()];
+        let mut ball = nbhds[i].clone();
+        ball.extend(dists.into_keys().filter(|&vtx| vtx as usize != i));
 
-    for (vtx_idx, vtx_nbs_set) in nbhds.into_iter() {
-        ret[vtx_idx as usize] = vtx_nbs_set;
-    }
+        ball
+    })
+}
 
-    ret
+/// Aumenta as vizinhanças dos vértices do bordo em mais um anel.
+///
+/// A vizinhança de um vértice do bordo só tem pontos de um lado dele, e
+/// por isso tem cerca de metade dos pontos da vizinhança de um vértice
+/// interior. O ajuste do parabolóide fica mal condicionado, especialmente
+/// na direção perpendicular ao bordo. Acrescentando os vizinhos dos
+/// vizinhos (que também estão do lado de dentro), o ajuste passa a ter
+/// pontos suficientes, ainda que de um lado só.
+///
+/// `fit_nbhds` são as vizinhanças usadas no ajuste, que são alteradas.
+pub fn widen_boundary_neighborhoods(mesh: &HalfEdgeMesh, fit_nbhds: &mut [BTreeSet<u32>]) {
+    par::for_each_mut(fit_nbhds, |i, fit_nbhd| {
+        if !mesh.is_boundary_vertex(i) {
+            return;
+        }
+
+        let ring = fit_nbhd
+            .iter()
+            .flat_map(|&nb| mesh.one_ring(nb as usize))
+            .filter(|&nb| nb != i)
+            .map(|nb| nb as u32)
+            .collect::<Vec<_>>();
+
+        fit_nbhd.extend(ring);
+    });
 }
 
 /// Calcula as normais médias para cada vértice.
@@ -161,47 +474,185 @@ pub fn compute_neighborhoods(mesh: &tobj::Mesh) -> Vec<BTreeSet<u32>> {
 /// para todas as faces que contém cada ponto, e tomamos a média dos vetores
 /// normais associados.
 ///
-/// Aqui é uma oportunidade de melhoria da precisão do programa. Podemos tomar a
-/// média ponderada considerando a área de cada triângulo, por exemplo. Mas,
-/// aqui apenas tomamos a média aritmética.
-pub fn compute_avg_normals(mesh: &tobj::Mesh) -> Vec<Vec3> {
-    let mut normals = HashMap::new();
-
-    for i in 0..mesh.indices.len() {
-        // Olhamos para cada vértice considerando cada triângulo, e acumulamos
-        // os vetores normais associados a cada vértice.
-        let vertex_idx =  mesh.indices[i];
-        let normal_idx =  mesh.normal_indices[i];
-
-        let entry = normals.entry(vertex_idx).or_insert(Vec::new());
-
-        entry.push(Vec3::new(
-            mesh.normals[3 * normal_idx as usize + 0],
-            mesh.normals[3 * normal_idx as usize + 1],
-            mesh.normals[3 * normal_idx as usize + 2],
-        ));
+/// Aqui apenas tomamos a média aritmética. Médias ponderadas, calculadas a
+/// partir da geometria das faces, estão em [`compute_weighted_normals`].
+///
+/// Muitos arquivos `.obj` (especialmente os vindos de escaneamentos) não
+/// têm normais. Nesse caso (ver [`HalfEdgeMesh::has_normals`]), calculamos
+/// as normais a partir das faces, ponderadas pela área.
+pub fn compute_avg_normals(mesh: &HalfEdgeMesh) -> Vec<Vec3> {
+    if !mesh.has_normals() {
+        return compute_weighted_normals(mesh, NormalWeighting::Area);
     }
 
-    let mut ret = vec![Vec3::zero(); mesh.positions.len()/3];
+    par::map(mesh.n_vertices(), |v| {
+        // Olhamos para cada vértice considerando cada triângulo (ou seja,
+        // para cada canto), e acumulamos os vetores normais associados a
+        // cada vértice.
+        let (sum, count) = mesh
+            .corners(v)
+            .fold((Vec3::zero(), 0usize), |(sum, count), c| (sum + mesh.corner_normal(c).unwrap(), count + 1));
+
+        // Tiramos a média...
+        let average = sum / count as f32;
+
+        // ...e normalizamos.
+        average.normalized()
+    })
+}
 
-    // Cálculo da média e housekeeping.
-    for i in 0..(mesh.positions.len()/3) {
-        let vtx_normals = normals
-            .entry(i as u32)
-            .or_default();
+/// Como ponderar as normais das faces em [`compute_weighted_normals`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NormalWeighting {
+    /// Todas as faces incidentes têm o mesmo peso.
+    Uniform,
+    /// Cada face pesa proporcionalmente à sua área.
+    #[default]
+    Area,
+    /// Cada face pesa proporcionalmente ao ângulo que ela faz no vértice.
+    Angle,
+    /// Os pesos de Max (1999), exatos para vértices sobre uma esfera.
+    Max,
+}
 
-        let mut average = Vec3::zero();
+/// Calcula a normal de cada vértice a partir da geometria das faces.
+///
+/// Diferente de [`compute_avg_normals`], aqui ignoramos as normais do
+/// arquivo `.obj`. Para cada face incidente no vértice calculamos a normal
+/// da face pelo produto vetorial $e_1 \times e_2$ das duas arestas que saem
+/// do vértice, e tomamos uma média ponderada dessas normais. Os pesos são:
+///
+/// - `Uniform`: 1 para cada face;
+/// - `Area`: a área da face (que é o que já obtemos sem normalizar o
+///   produto vetorial);
+/// - `Angle`: o ângulo entre $e_1$ e $e_2$, o que torna o resultado
+///   independente de como a região em volta do vértice foi triangulada;
+/// - `Max`: $\sin \theta / (|e_1| |e_2|)$, ou seja, o produto vetorial
+///   dividido por $|e_1|^2 |e_2|^2$. Faces com arestas curtas pesam mais.
+///
+/// O sentido das normais vem da ordem dos vértices nas faces (regra da
+/// mão direita), como nas normais do `.obj`.
+pub fn compute_weighted_normals(mesh: &HalfEdgeMesh, weighting: NormalWeighting) -> Vec<Vec3> {
+    par::map(mesh.n_vertices(), |v| {
+        let mut sum = Vec3::zero();
 
-        for normal in vtx_normals.iter() {
-            average += *normal;
+        for c in mesh.corners(v) {
+            // As arestas que saem do canto, na ordem da face.
+            let p = mesh.position(v);
+            let e1 = mesh.position(mesh.target(c)) - p;
+            let e2 = mesh.position(mesh.opposite_vertex(c)) - p;
+
+            let cross = e1.cross(e2);
+
+            let contribution = match weighting {
+                NormalWeighting::Uniform => cross.normalized(),
+                NormalWeighting::Area => cross,
+                NormalWeighting::Angle => {
+                    let angle = e1.normalized().dot(e2.normalized()).clamp(-1.0, 1.0).acos();
+
+                    angle * cross.normalized()
+                }
+                NormalWeighting::Max => cross / (e1.mag_sq() * e2.mag_sq()),
+            };
+
+            // Faces degeneradas (área zero) dariam NaN ao normalizar.
+            if contribution.x.is_finite() && contribution.y.is_finite() && contribution.z.is_finite() {
+                sum += contribution;
+            }
         }
 
-        // Tiramos a média...
-        average /= vtx_normals.len() as f32;
+        sum.normalized()
+    })
+}
 
-        // ...e normalizamos.
-        ret[i] = average.normalized();
-    }
+/// Calcula a normal de cada vértice por análise de componentes principais
+/// (PCA) da sua vizinhança.
+///
+/// Tomamos o vértice e seus vizinhos como uma nuvem de pontos, e calculamos
+/// a matriz de covariância $C$ desses pontos. Os autovetores de $C$ são as
+/// direções em que a nuvem se espalha; como os pontos estão
+/// (aproximadamente) sobre um plano, o autovetor de menor autovalor é a
+/// direção em que eles *menos* se espalham, isto é, a normal do plano.
+///
+/// Esse método não usa as faces, só a vizinhança, e por isso não sabe para
+/// que lado a normal aponta. Escolhemos o sentido que concorda com as normais
+/// das faces ([`compute_weighted_normals`]).
+pub fn compute_pca_normals(mesh: &HalfEdgeMesh, nbhds: &[BTreeSet<u32>]) -> Vec<Vec3> {
+    let face_normals = compute_weighted_normals(mesh, NormalWeighting::Area);
+
+    par::map(mesh.n_vertices(), |i| {
+        let points = std::iter::once(i)
+            .chain(nbhds[i].iter().map(|&nb| nb as usize))
+            .map(|idx| mesh.position(idx))
+            .collect::<Vec<_>>();
+
+        let centroid = points.iter().fold(Vec3::zero(), |acc, p| acc + *p) / points.len() as f32;
+
+        // Matriz de covariância (a menos de um fator, que não muda os
+        // autovetores).
+        let mut cov = Mat3::new(Vec3::zero(), Vec3::zero(), Vec3::zero());
+        for p in points.iter() {
+            let d = *p - centroid;
+            cov += Mat3::new(d * d.x, d * d.y, d * d.z);
+        }
+
+        let n = smallest_eigenvector(&cov);
+
+        if n.dot(face_normals[i]) < 0.0 { -n } else { n }
+    })
+}
+
+/// Calcula o autovetor (unitário) associado ao menor autovalor de uma
+/// matriz simétrica 3x3.
+///
+/// Os autovalores são calculados pela fórmula fechada trigonométrica para
+/// matrizes simétricas. Sendo $\lambda$ o menor deles, as linhas de
+/// $M = C - \lambda I$ são todas perpendiculares ao autovetor procurado,
+/// então o produto vetorial de duas delas é paralelo a ele. Usamos o maior
+/// dos três produtos possíveis, que é o mais estável numericamente.
+fn smallest_eigenvector(m: &Mat3) -> Vec3 {
+    use std::f32::consts::PI;
+
+    let [c0, c1, c2] = m.cols;
+    let (a00, a11, a22) = (c0.x, c1.y, c2.z);
+    let (a01, a02, a12) = (c1.x, c2.x, c2.y);
+
+    let off = a01 * a01 + a02 * a02 + a12 * a12;
+    let q = (a00 + a11 + a22) / 3.0;
+
+    let lambda = if off == 0.0 {
+        // Matriz diagonal: os autovalores são a própria diagonal.
+        a00.min(a11).min(a22)
+    } else {
+        let p2 = (a00 - q).powi(2) + (a11 - q).powi(2) + (a22 - q).powi(2) + 2.0 * off;
+        let p = (p2 / 6.0).sqrt();
+
+        let b = (1.0 / p) * shift_diagonal(m, -q);
+        let r = (0.5 * b.determinant()).clamp(-1.0, 1.0);
+        let phi = r.acos() / 3.0;
+
+        // O menor dos três autovalores.
+        q + 2.0 * p * (phi + 2.0 * PI / 3.0).cos()
+    };
+
+    let shifted = shift_diagonal(m, -lambda);
+    // A matriz é simétrica, então as colunas são as linhas.
+    let [r0, r1, r2] = shifted.cols;
+
+    [r0.cross(r1), r0.cross(r2), r1.cross(r2)]
+        .into_iter()
+        .max_by(|a, b| a.mag_sq().total_cmp(&b.mag_sq()))
+        .unwrap()
+        .normalized()
+}
+
+/// Calcula $M + sI$.
+fn shift_diagonal(m: &Mat3, s: f32) -> Mat3 {
+    let mut ret = *m;
+
+    ret.cols[0].x += s;
+    ret.cols[1].y += s;
+    ret.cols[2].z += s;
 
     ret
 }
@@ -213,50 +664,81 @@ pub fn compute_avg_normals(mesh: &tobj::Mesh) -> Vec<Vec3> {
 /// ao ponto. Como queremos fazer contas, resta descobrir uma base para tal plano.
 ///
 /// A ideia é simples: tome um vetor a' qualquer, tal que a' não seja paralelo
-/// ao plano tangente. Agora, considere a projeção de a' no plano tangente (o que
+/// ao vetor normal. Agora, considere a projeção de a' no plano tangente (o que
 /// podemos fazer, a partir do vetor normal), e chame de a tal projeção normalizada.
 /// Note que tal vetor a faz parte do plano tangente. Então, tome b como sendo a
 /// normalização do produto vetorial de a e n. Com essa construção, { a, b } será
 /// uma base para o plano tangente.
 ///
+/// Como a', usamos a aresta que liga o vértice a um de seus vizinhos, que está
+/// quase contida no plano tangente. Se o vértice não tem vizinhos (ou se a
+/// aresta for paralela à normal), usamos o eixo coordenado menos alinhado com
+/// a normal.
+///
 /// Para facilitarmos as computações, retornamos, na verdade, bases para todo o R^3.
 /// Tais bases estão na forma de matrizes 3x3, onde cada coluna na matriz é um dos
 /// vetores da base, representados por sua vez na base canônica do R^3.
 pub fn compute_tangent_basis(
-    mesh: &tobj::Mesh,
-    nbhds: &Vec<BTreeSet<u32>>,
-    normals: &Vec<Vec3>,
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    normals: &[Vec3],
 ) -> Vec<Mat3> {
-    let mut ret = vec![Mat3::identity(); mesh.positions.len()/3];
-
-    for i in 0..(mesh.positions.len()/3) {
+    par::map(mesh.n_vertices(), |i| {
         // Seja i o vértice p da malha.
-        //
-        // Seja `nbhds` o conjunto de vértices adjacentes a p.
-        let nbhds = nbhds.get(i).unwrap();
+        let p = mesh.position(i);
 
         // Seja `n` o vetor normal associado ao vértice p.
         let n = normals[i];
 
-        // Escolhe um outro vertice arbitrario na vizinhanca do vertice atual
-        let a_tilde_idx = *nbhds.iter().next().unwrap() as usize;
-        let a_tilde = Vec3::new(
-            mesh.positions[3*a_tilde_idx + 0],
-            mesh.positions[3*a_tilde_idx + 1],
-            mesh.positions[3*a_tilde_idx + 2],
-        );
+        // Projeta um vetor no plano tangente, se ele não for (quase)
+        // paralelo à normal.
+        let project = |a_tilde: Vec3| {
+            let a = a_tilde - n * a_tilde.dot(n);
+
+            if a.mag_sq() > 1e-12 * a_tilde.mag_sq() { Some(a.normalized()) } else { None }
+        };
+
+        // Escolhe um outro vertice arbitrario na vizinhanca do vertice atual,
+        // e calcula a projeção da aresta até ele no plano tangente.
+        let a = nbhds[i]
+            .iter()
+            .next()
+            .and_then(|&nb| project(mesh.position(nb as usize) - p))
+            .unwrap_or_else(|| {
+                let axis = if n.x.abs() <= n.y.abs() && n.x.abs() <= n.z.abs() {
+                    Vec3::unit_x()
+                } else if n.y.abs() <= n.z.abs() {
+                    Vec3::unit_y()
+                } else {
+                    Vec3::unit_z()
+                };
 
-        // Calcula a projeção de a' no plano tangente
-        let a = (a_tilde - n * a_tilde.dot(n)).normalized();
+                // Se nem isso funcionar, a normal é inválida (NaN), e a base
+                // também será.
+                project(axis).unwrap_or(axis)
+            });
 
         // Calcula o vetor b
         let b = n.cross(a).normalized();
 
         // Entao, {a, b, n} é uma base de R^3!!! Em particular, {a, b} é base de TpS!!!!!
-        ret[i] = Mat3::new(a, b, n);
-    }
+        Mat3::new(a, b, n)
+    })
+}
 
-    ret
+/// Como ponderar cada vizinho no ajuste do parabolóide feito em
+/// [`compute_shape_operator_weighted`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FitWeighting {
+    /// Todos os vizinhos têm o mesmo peso.
+    #[default]
+    Uniform,
+    /// Cada vizinho tem peso $1/|p_i - p|^2$: vizinhos mais próximos
+    /// pesam mais.
+    InverseDistance,
+    /// Cada vizinho tem peso igual à sua área baricêntrica (ver
+    /// [`compute_vertex_areas`]), o que compensa triangulações irregulares.
+    Area,
 }
 
 /// Calcula o Shape Operator para cada vértice.
@@ -265,102 +747,164 @@ pub fn compute_tangent_basis(
 ///
 /// Consideramos os vértices, as normais e as bases dos planos tangentes
 /// calculados acima e fazemos o ajuste de um parabolóide tal que seja
-/// definido a partir do plano tangente e contenha os vértices vizinhos.
+/// definido a partir do plano tangente e passe o mais perto possível dos
+/// vértices vizinhos.
 ///
 /// Vamos considerar o parabolóide
 ///
 /// $$x(u,v) = \frac{1}{2} \left( au^2 + 2buv + cv^2 \right)$$
 ///
-/// Se $p$ é o vértice que estamos considerando, sejam $p_i$'s os vértices
+/// Se $p$ é o vértice que estamos considerando, sejam $p_i$'s os $n$ vértices
 /// adjacentes a $p$. Escreveremos cada $p_i$ tal que $p_i = x(u_i, v_i)$.
 ///
 /// Fazendo contas, chegaremos a um sistema de equações. Escrevendo esse
 /// sistema na forma matricial:
 ///
-/// - $U$ é a matriz 3x3 tal que as linhas de U são vetores da forma
+/// - $U$ é a matriz nx3 tal que as linhas de U são vetores da forma
 ///   $(u_i^2/2, u_i v_i, v_i^2/2)$
 /// - $X$ é o vetor coluna contendo os coeficientes $(a, b, c)$ do parabolóide
 /// - $F$ é o vetor coluna dos coeficientes em $N$ de cada $p_i$.
 ///
 /// O sistema é representado pela equação $UX = F$, e queremos descobrir $X$.
-/// De fato, $X = U^-1 F$. Mas, na prática, usamos a pseudo-inversa para
-/// considerar os casos em que $det U = 0$ (o que pode acontecer dada as
-/// construções que fizemos). Chegaremos em $X = ((U' U)^-1)U' F$.
+/// Como em geral temos mais de 3 vizinhos, o sistema é sobredeterminado, e
+/// procuramos a solução de quadrados mínimos, dada pelas equações normais:
+/// $X = ((U' U)^-1)U' F$.
+///
+/// Usamos todos os vizinhos, e não só três deles: assim o resultado não
+/// depende da numeração dos vértices, e o ruído diminui bastante em vértices
+/// com muitos vizinhos.
+///
+/// Note que não precisamos montar $U$ explicitamente. Como
+/// $U' U = \sum_i u_i u_i'$ e $U' F = \sum_i u_i f_i$, onde $u_i$ é a
+/// i-ésima linha de $U$, basta acumular essas somas, que são de tamanho fixo.
 ///
 /// Tendo $X$, a parametrização do parabolóide fica completamente determinada,
 /// e podemos fazer contas e chegar na matriz do shape operator, o que é o desejado.
-#[allow(non_snake_case)]
+///
+/// Esse método dá o mesmo peso a todos os vizinhos. Para outras ponderações,
+/// veja [`compute_shape_operator_weighted`].
 pub fn compute_shape_operator(
-    mesh: &tobj::Mesh,
-    nbhds: &Vec<BTreeSet<u32>>,
-    tangent_bases: &Vec<Mat3>,
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    tangent_bases: &[Mat3],
 ) -> Vec<Mat2> {
-    let mut ret = vec![Mat2::identity(); mesh.positions.len()/3];
+    compute_shape_operator_weighted(mesh, nbhds, tangent_bases, FitWeighting::Uniform)
+}
+
+/// Calcula o Shape Operator para cada vértice, ponderando os vizinhos.
+///
+/// Igual a [`compute_shape_operator`], mas resolvemos o problema de
+/// quadrados mínimos ponderado: se $W$ é a matriz diagonal com os pesos
+/// $w_i$ de cada vizinho, então $X = ((U' W U)^-1)U' W F$.
+///
+/// Vértices em que o ajuste não pode ser feito ficam com uma matriz de
+/// `NaN`s. Para saber quais são e por quê, use
+/// [`compute_shape_operator_checked`].
+pub fn compute_shape_operator_weighted(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    tangent_bases: &[Mat3],
+    weighting: FitWeighting,
+) -> Vec<Mat2> {
+    let nan = Mat2::new(Vec2::broadcast(f32::NAN), Vec2::broadcast(f32::NAN));
+
+    compute_shape_operator_checked(mesh, nbhds, tangent_bases, weighting)
+        .into_iter()
+        .map(|shape_op| shape_op.unwrap_or(nan))
+        .collect()
+}
+
+/// Calcula o Shape Operator para cada vértice, indicando os vértices em que
+/// o ajuste falhou.
+///
+/// O ajuste do parabolóide tem 3 incógnitas, então precisamos de pelo
+/// menos 3 vizinhos, e eles não podem estar numa configuração degenerada
+/// (por exemplo, todos sobre uma mesma reta que passa pelo vértice). Nesses
+/// casos a matriz $U' W U$ é singular (ou quase), e em vez de devolver o
+/// resultado de invertê-la (infinitos e `NaN`s), devolvemos o motivo da
+/// falha como um [`VertexStatus`].
+#[allow(non_snake_case)]
+pub fn compute_shape_operator_checked(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    tangent_bases: &[Mat3],
+    weighting: FitWeighting,
+) -> Vec<Result<Mat2, VertexStatus>> {
+    let areas = match weighting {
+        FitWeighting::Area => compute_vertex_areas(mesh),
+        _ => Vec::new(),
+    };
 
     // Seja v o vértice de índice i.
-    for i in 0..(mesh.positions.len()/3) {
+    par::map(mesh.n_vertices(), |i| {
         let nbhds = nbhds.get(i).unwrap();
 
-        let v = Vec3::new(
-            mesh.positions[3*i + 0],
-            mesh.positions[3*i + 1],
-            mesh.positions[3*i + 2],
-        );
-
-        let mut nbhds = nbhds.iter();
+        if nbhds.is_empty() {
+            return Err(VertexStatus::Isolated);
+        }
 
-        // Considere os vizinhos v_1, v_2, v_3 de v.
-        let nb1_idx = *(nbhds.next().unwrap()) as usize;
-        let nb2_idx = *(nbhds.next().unwrap()) as usize;
-        let nb3_idx = *(nbhds.next().unwrap()) as usize;
+        if nbhds.len() < 3 {
+            return Err(VertexStatus::TooFewNeighbours);
+        }
 
-        let nb1_vtx = Vec3::new(
-            mesh.positions[3*nb1_idx + 0],
-            mesh.positions[3*nb1_idx + 1],
-            mesh.positions[3*nb1_idx + 2],
-        );
-        let nb2_vtx = Vec3::new(
-            mesh.positions[3*nb2_idx + 0],
-            mesh.positions[3*nb2_idx + 1],
-            mesh.positions[3*nb2_idx + 2],
-        );
-        let nb3_vtx = Vec3::new(
-            mesh.positions[3*nb3_idx + 0],
-            mesh.positions[3*nb3_idx + 1],
-            mesh.positions[3*nb3_idx + 2],
-        );
+        let v = mesh.position(i);
 
         // Observa a base de TvS
         let tps_basis = tangent_bases[i];
         let tps_basis_t = tps_basis.transposed();
 
-        // Calcula as coordenadas dos vizinhos na base do TvS.
-        let nb1_local = tps_basis_t * (nb1_vtx - v);
-        let nb2_local = tps_basis_t * (nb2_vtx - v);
-        let nb3_local = tps_basis_t * (nb3_vtx - v);
-
-        // Extrai o vetor tangente da matriz base do TvS.
+        // Extrai o vetor normal da matriz base do TvS.
         let n = tps_basis.cols[2];
 
-        // Calcula a distância de cada vizinho até o plano tangente.
-        // Esses serão valores alcançados pela imagem do parabolóide.
-        let nb1_h = n.dot(nb1_vtx - v);
-        let nb2_h = n.dot(nb2_vtx - v);
-        let nb3_h = n.dot(nb3_vtx - v);
-
-        // Monta a matriz U dos parâmetros do parabolóide.
-        let U = Mat3::new(
-            0.5 * Vec3::new(nb1_local.x.powi(2), nb2_local.x.powi(2), nb3_local.x.powi(2)),
-            Vec3::new(nb1_local.x * nb1_local.y, nb2_local.x * nb2_local.y, nb3_local.x * nb3_local.y),
-            0.5 * Vec3::new(nb1_local.y.powi(2), nb2_local.y.powi(2), nb3_local.y.powi(2)),
-        );
+        // Acumuladores de U' W U e U' W F.
+        let mut UtU = Mat3::new(Vec3::zero(), Vec3::zero(), Vec3::zero());
+        let mut UtF = Vec3::zero();
+
+        for nb_idx in nbhds.iter() {
+            let nb_idx = *nb_idx as usize;
+
+            let nb_vtx = mesh.position(nb_idx);
+
+            // Calcula as coordenadas do vizinho na base do TvS.
+            let nb_local = tps_basis_t * (nb_vtx - v);
+
+            // Calcula a distância do vizinho até o plano tangente.
+            // Esse será um valor alcançado pela imagem do parabolóide.
+            let nb_h = n.dot(nb_vtx - v);
 
-        // Monta a matriz F dos valores alcançados.
-        let F = Vec3::new(nb1_h, nb2_h, nb3_h);
+            let w = match weighting {
+                FitWeighting::Uniform => 1.0,
+                FitWeighting::InverseDistance => 1.0 / (nb_vtx - v).mag_sq(),
+                FitWeighting::Area => areas[nb_idx],
+            };
+
+            // Linha de U correspondente a esse vizinho.
+            let u = Vec3::new(
+                0.5 * nb_local.x.powi(2),
+                nb_local.x * nb_local.y,
+                0.5 * nb_local.y.powi(2),
+            );
+
+            // Soma w * u u' (produto externo, coluna por coluna) e w * u f.
+            UtU += Mat3::new(u * (w * u.x), u * (w * u.y), u * (w * u.z));
+            UtF += u * (w * nb_h);
+        }
+
+        // Se U' W U é (quase) singular, os vizinhos não determinam o
+        // parabolóide. Comparamos o determinante com o traço para que o
+        // teste não dependa da escala da malha.
+        let scale = (UtU.cols[0].x + UtU.cols[1].y + UtU.cols[2].z) / 3.0;
+        if UtU.determinant().abs() <= 1e-6 * scale.powi(3) {
+            return Err(VertexStatus::SingularFit);
+        }
 
         // Por fim, calcula a matriz dos coeficientes que determinam
         // completamente a parametrização.
-        let X = (((U.transposed() * U).inversed()) * U.transposed()) * F;
+        let X = UtU.inversed() * UtF;
+
+        if !(X.x.is_finite() && X.y.is_finite() && X.z.is_finite()) {
+            return Err(VertexStatus::SingularFit);
+        }
 
         // Monta a matriz do shape operator. Ela é da forma
         //
@@ -371,10 +915,268 @@ pub fn compute_shape_operator(
             Vec2::new(X.y, X.z),
         );
 
-        ret[i] = S;
-    }
+        Ok(S)
+    })
+}
 
-    ret
+/// Precisão das contas do pipeline (ver [`crate::pipeline`]).
+///
+/// As posições da malha e os resultados continuam em `f32`; o que muda é a
+/// precisão de todas as contas intermediárias, das normais ao ajuste e aos
+/// estimadores discretos, que é onde os dígitos se perdem (ver a seção
+/// "Precisão" acima e o módulo [`crate::double`]).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    /// Precisão simples (`f32`).
+    #[default]
+    Single,
+    /// Precisão dupla (`f64`, ver [`crate::double`]).
+    Double,
+}
+
+/// Igual a [`compute_shape_operator_checked`], mas montando e resolvendo
+/// os ajustes em precisão dupla.
+///
+/// As coordenadas dos vizinhos são convertidas para `f64` antes de
+/// qualquer conta, inclusive da diferença até o vértice, e só o Shape
+/// Operator final volta para `f32`. As bases tangentes, por outro lado,
+/// vêm em `f32`; para fazer todas as etapas em `f64`, use as funções de
+/// [`crate::double`]. O custo em relação à versão em `f32` é medido em
+/// `benches/shape_operator.rs`.
+pub fn compute_shape_operator_f64(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    tangent_bases: &[Mat3],
+    weighting: FitWeighting,
+) -> Vec<Result<Mat2, VertexStatus>> {
+    let tangent_bases = tangent_bases
+        .iter()
+        .map(|basis| {
+            let [a, b, n] = basis.cols.map(double::to_f64);
+
+            DMat3::new(a, b, n)
+        })
+        .collect::<Vec<_>>();
+
+    double::compute_shape_operator(mesh, nbhds, &tangent_bases, weighting)
+        .into_iter()
+        .map(|fit| {
+            fit.map(|shape_op| {
+                let [c0, c1] = shape_op.cols;
+
+                Mat2::new(
+                    Vec2::new(c0.x as f32, c0.y as f32),
+                    Vec2::new(c1.x as f32, c1.y as f32),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Como resolver os sistemas do ajuste do parabolóide.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FitSolver {
+    /// Um vértice de cada vez ([`compute_shape_operator_checked`]).
+    #[default]
+    Scalar,
+    /// Oito vértices de cada vez, com instruções SIMD
+    /// ([`compute_shape_operator_simd`]).
+    Simd,
+}
+
+/// Igual a [`compute_shape_operator_checked`], mas resolvendo os ajustes de
+/// oito vértices ao mesmo tempo.
+///
+/// Os tipos "largos" do [`ultraviolet`] (`Vec3x8`, `Mat3x8`) guardam oito
+/// vetores ou matrizes, um em cada *lane*, e cada operação sobre eles é
+/// feita nas oito lanes de uma vez por instruções SIMD. Juntamos então oito
+/// vértices consecutivos: a cada passo, pegamos o próximo vizinho de cada
+/// um deles, e acumulamos $U' W U$ e $U' W F$ das oito lanes juntas. No
+/// final, os oito determinantes e as oito inversas também saem de uma vez.
+///
+/// Os vértices têm números diferentes de vizinhos, então as lanes cujo
+/// vértice já acabou continuam com um "vizinho" nulo de peso zero, que não
+/// muda as somas.
+///
+/// Os resultados são os mesmos de [`compute_shape_operator_checked`], a
+/// menos de arredondamentos: dependendo do processador, algumas
+/// multiplicações seguidas de somas são feitas numa única operação, com um
+/// arredondamento a menos. Em vértices cujo ajuste é quase singular, as
+/// equações normais amplificam essas diferenças, e os dois métodos podem
+/// discordar já na segunda ou terceira casa decimal.
+#[allow(non_snake_case)]
+pub fn compute_shape_operator_simd(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    tangent_bases: &[Mat3],
+    weighting: FitWeighting,
+) -> Vec<Result<Mat2, VertexStatus>> {
+    const LANES: usize = 8;
+
+    let n_vertices = mesh.n_vertices();
+
+    let areas = match weighting {
+        FitWeighting::Area => compute_vertex_areas(mesh),
+        _ => Vec::new(),
+    };
+
+    let batches = par::map(n_vertices.div_ceil(LANES), |batch| {
+        // Os vértices de cada lane. A última leva pode ter menos de oito
+        // vértices; as lanes que sobram repetem o último, e são descartadas
+        // no final.
+        let first = batch * LANES;
+        let lanes: [usize; LANES] = std::array::from_fn(|l| (first + l).min(n_vertices - 1));
+
+        let v = lanes.map(|i| mesh.position(i));
+        let basis = Mat3x8::new(
+            Vec3x8::from(lanes.map(|i| tangent_bases[i].cols[0])),
+            Vec3x8::from(lanes.map(|i| tangent_bases[i].cols[1])),
+            Vec3x8::from(lanes.map(|i| tangent_bases[i].cols[2])),
+        );
+        let basis_t = basis.transposed();
+        let n = basis.cols[2];
+
+        let mut nb_iters = lanes.map(|i| nbhds[i].iter());
+        let steps = lanes.iter().map(|&i| nbhds[i].len()).max().unwrap_or(0);
+
+        let zero = Vec3x8::zero();
+        let half = f32x8::splat(0.5);
+
+        let mut UtU = Mat3x8::new(zero, zero, zero);
+        let mut UtF = zero;
+
+        for _ in 0..steps {
+            let mut d = [Vec3::zero(); LANES];
+            let mut w = [0.0f32; LANES];
+
+            for l in 0..LANES {
+                if let Some(&nb_idx) = nb_iters[l].next() {
+                    let nb_idx = nb_idx as usize;
+
+                    d[l] = mesh.position(nb_idx) - v[l];
+                    w[l] = match weighting {
+                        FitWeighting::Uniform => 1.0,
+                        FitWeighting::InverseDistance => 1.0 / d[l].mag_sq(),
+                        FitWeighting::Area => areas[nb_idx],
+                    };
+                }
+            }
+
+            let d = Vec3x8::from(d);
+            let w = f32x8::from(w);
+
+            // As mesmas contas de `compute_shape_operator_checked`, nas oito
+            // lanes.
+            let nb_local = basis_t * d;
+            let nb_h = n.dot(d);
+
+            let u = Vec3x8::new(
+                half * nb_local.x * nb_local.x,
+                nb_local.x * nb_local.y,
+                half * nb_local.y * nb_local.y,
+            );
+
+            UtU += Mat3x8::new(u * (w * u.x), u * (w * u.y), u * (w * u.z));
+            UtF += u * (w * nb_h);
+        }
+
+        let det: [f32; LANES] = UtU.determinant().into();
+        let trace: [f32; LANES] = (UtU.cols[0].x + UtU.cols[1].y + UtU.cols[2].z).into();
+        let X: [Vec3; LANES] = (UtU.inversed() * UtF).into();
+
+        // Os testes são feitos lane a lane, na mesma ordem do caso escalar.
+        let results: [Result<Mat2, VertexStatus>; LANES] = std::array::from_fn(|l| {
+            let len = nbhds[lanes[l]].len();
+
+            if len == 0 {
+                return Err(VertexStatus::Isolated);
+            }
+
+            if len < 3 {
+                return Err(VertexStatus::TooFewNeighbours);
+            }
+
+            let scale = trace[l] / 3.0;
+            if det[l].abs() <= 1e-6 * scale.powi(3) {
+                return Err(VertexStatus::SingularFit);
+            }
+
+            let X = X[l];
+
+            if !(X.x.is_finite() && X.y.is_finite() && X.z.is_finite()) {
+                return Err(VertexStatus::SingularFit);
+            }
+
+            Ok(-1.0 * Mat2::new(
+                Vec2::new(X.x, X.y),
+                Vec2::new(X.y, X.z),
+            ))
+        });
+
+        results
+    });
+
+    batches.into_iter().flatten().take(n_vertices).collect()
+}
+
+/// Calcula o resíduo do ajuste do parabolóide em cada vértice.
+///
+/// O resíduo é a raiz da média (ponderada, com os mesmos pesos do ajuste)
+/// dos quadrados das distâncias, na direção da normal, entre cada vizinho e
+/// o parabolóide ajustado. Ele tem a mesma unidade das posições, e mede o
+/// quanto a vizinhança se afasta de uma quádrica: é grande em vincos, em
+/// cantos e em regiões com ruído, onde as curvaturas são menos confiáveis.
+///
+/// Vértices cujo Shape Operator não é finito ficam com `NaN`.
+pub fn compute_fit_residuals(
+    mesh: &HalfEdgeMesh,
+    nbhds: &[BTreeSet<u32>],
+    tangent_bases: &[Mat3],
+    shape_ops: &[Mat2],
+    weighting: FitWeighting,
+) -> Vec<f32> {
+    let areas = match weighting {
+        FitWeighting::Area => compute_vertex_areas(mesh),
+        _ => Vec::new(),
+    };
+
+    par::map(mesh.n_vertices(), |i| {
+        // O Shape Operator é -X, onde X = (a, b, c) são os coeficientes do
+        // parabolóide (ver compute_shape_operator).
+        let shape = &shape_ops[i];
+        let (a, b, c) = (-shape.cols[0].x, -shape.cols[0].y, -shape.cols[1].y);
+
+        if !(a.is_finite() && b.is_finite() && c.is_finite()) {
+            return f32::NAN;
+        }
+
+        let v = mesh.position(i);
+        let tps_basis_t = tangent_bases[i].transposed();
+        let n = tangent_bases[i].cols[2];
+
+        let mut sum = 0.0;
+        let mut total_weight = 0.0;
+
+        for &nb_idx in &nbhds[i] {
+            let nb_idx = nb_idx as usize;
+            let d = mesh.position(nb_idx) - v;
+            let local = tps_basis_t * d;
+
+            let w = match weighting {
+                FitWeighting::Uniform => 1.0,
+                FitWeighting::InverseDistance => 1.0 / d.mag_sq(),
+                FitWeighting::Area => areas[nb_idx],
+            };
+
+            let fitted = 0.5 * a * local.x * local.x + b * local.x * local.y + 0.5 * c * local.y * local.y;
+            let err = n.dot(d) - fitted;
+
+            sum += w * err * err;
+            total_weight += w;
+        }
+
+        (sum / total_weight).sqrt()
+    })
 }
 
 /// Calcula as curvaturas para cada vértice.
@@ -383,21 +1185,421 @@ pub fn compute_shape_operator(
 /// a matriz do Shape Operator,
 ///
 /// - $K = det(S)$
-/// - $H = tr(S)$
+/// - $H = tr(S)/2$
+///
+/// Ou seja, K é o produto e H é a média das curvaturas principais (ver
+/// [`compute_principal_directions`]).
 pub fn compute_curvatures(
-    shape_ops: &Vec<Mat2>,
+    shape_ops: &[Mat2],
 ) -> Vec<(f32, f32)> {
     fn trace(m: &Mat2) -> f32 {
-        m.cols[0].x * m.cols[1].y
+        m.cols[0].x + m.cols[1].y
     }
 
-    shape_ops
-        .iter()
-        .map(|shape| {
-            let k = shape.determinant();
-            let h = trace(&shape);
+    par::map(shape_ops.len(), |i| {
+        let shape = &shape_ops[i];
 
-            (k, h)
-        })
-        .collect()
+        let k = shape.determinant();
+        let h = 0.5 * trace(shape);
+
+        (k, h)
+    })
+}
+
+/// Calcula as curvaturas principais para cada vértice.
+///
+/// As curvaturas principais são os autovalores do Shape Operator. Como a
+/// matriz $S$ que montamos é simétrica, seus autovalores são reais, e são
+/// as raízes do polinômio característico
+///
+/// $$\lambda^2 - tr(S) \lambda + det(S) = 0$$
+///
+/// Retornamos os pares $(k_1, k_2)$ com $k_1 \leq k_2$.
+pub fn compute_principal_curvatures(
+    shape_ops: &[Mat2],
+) -> Vec<(f32, f32)> {
+    par::map(shape_ops.len(), |i| {
+        let shape = &shape_ops[i];
+
+        let half_tr = 0.5 * (shape.cols[0].x + shape.cols[1].y);
+        let det = shape.determinant();
+
+        // O discriminante é não-negativo para matrizes simétricas, mas
+        // erros de arredondamento podem deixá-lo levemente negativo.
+        let disc = (half_tr * half_tr - det).max(0.0).sqrt();
+
+        (half_tr - disc, half_tr + disc)
+    })
+}
+
+/// Curvaturas e direções principais de um vértice.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrincipalCurvatures {
+    /// Menor curvatura principal.
+    pub k1: f32,
+    /// Maior curvatura principal.
+    pub k2: f32,
+    /// Direção (unitária, no espaço do mundo) em que a curvatura é `k1`.
+    pub d1: Vec3,
+    /// Direção (unitária, no espaço do mundo) em que a curvatura é `k2`.
+    pub d2: Vec3,
+}
+
+impl PrincipalCurvatures {
+    /// Curvatura média, $(k_1 + k_2)/2$.
+    pub fn mean(&self) -> f32 {
+        0.5 * (self.k1 + self.k2)
+    }
+
+    /// Curvatura gaussiana, $k_1 k_2$.
+    pub fn gaussian(&self) -> f32 {
+        self.k1 * self.k2
+    }
+
+    /// Índice de forma de Koenderink,
+    /// $\frac{2}{\pi} atan \frac{k_2 + k_1}{k_2 - k_1}$ (ver [`shape_index`]).
+    pub fn shape_index(&self) -> f32 {
+        shape_index(self.k1, self.k2)
+    }
+}
+
+/// Índice de forma de Koenderink, a partir das curvaturas principais
+/// $k_1 \leq k_2$:
+///
+/// $$s = \frac{2}{\pi} atan \frac{k_2 + k_1}{k_2 - k_1}$$
+///
+/// Ele vai de -1 a 1 e não depende da escala da malha, só do "tipo" da
+/// forma: -1 numa concavidade esférica, -0,5 numa calha, 0 numa sela
+/// simétrica, 0,5 numa crista e 1 numa calota. Em pontos planos
+/// ($k_1 = k_2 = 0$) ele não está definido, e o resultado é `NaN`.
+pub fn shape_index(k1: f32, k2: f32) -> f32 {
+    if k1 == 0.0 && k2 == 0.0 {
+        return f32::NAN;
+    }
+
+    // atan2 em vez de atan da razão, para que pontos umbílicos
+    // (k1 = k2) dêem ±1 em vez de uma divisão por zero.
+    std::f32::consts::FRAC_2_PI * (k2 + k1).atan2(k2 - k1)
+}
+
+/// Calcula as curvaturas e as direções principais para cada vértice.
+///
+/// As direções principais são os autovetores do Shape Operator. Para a
+/// matriz simétrica
+///
+/// $$S = \begin{pmatrix} a & b \\ b & c \end{pmatrix}$$
+///
+/// o autovetor associado ao maior autovalor faz um ângulo
+/// $\theta = \frac{1}{2} atan2(2b, a - c)$ com o primeiro vetor da base de
+/// $T_pS$, e o outro autovetor é perpendicular a ele. Essa fórmula não tem
+/// problemas quando $b = 0$ ou quando os autovalores são iguais (num ponto
+/// umbílico qualquer direção é principal, e ela devolve uma delas).
+///
+/// Os autovetores estão escritos na base de $T_pS$ calculada por
+/// [`compute_tangent_basis`]; multiplicando pela base, os levamos de volta
+/// para o $\mathbb{R}^3$.
+pub fn compute_principal_directions(
+    shape_ops: &[Mat2],
+    tangent_bases: &[Mat3],
+) -> Vec<PrincipalCurvatures> {
+    let principal = compute_principal_curvatures(shape_ops);
+
+    par::map(shape_ops.len(), |i| {
+        let (shape, (k1, k2), basis) = (&shape_ops[i], principal[i], &tangent_bases[i]);
+
+        let a = shape.cols[0].x;
+        let b = shape.cols[0].y;
+        let c = shape.cols[1].y;
+
+        let theta = 0.5 * (2.0 * b).atan2(a - c);
+        let (sin, cos) = theta.sin_cos();
+
+        // Autovetores na base {a, b} do plano tangente.
+        let v2 = Vec2::new(cos, sin);
+        let v1 = Vec2::new(-sin, cos);
+
+        let to_world = |v: Vec2| basis.cols[0] * v.x + basis.cols[1] * v.y;
+
+        PrincipalCurvatures {
+            k1,
+            k2,
+            d1: to_world(v1),
+            d2: to_world(v2),
+        }
+    })
+}
+
+/// Calcula a área associada a cada vértice.
+///
+/// Usamos a área baricêntrica: cada triângulo contribui com um terço
+/// da sua área para cada um dos seus três vértices. Assim, a soma das
+/// áreas de todos os vértices é a área total da malha.
+///
+/// Essas áreas são úteis para integrar grandezas sobre a superfície,
+/// como a curvatura total (ver [`crate::stats::total_curvature`]).
+pub fn compute_vertex_areas(mesh: &HalfEdgeMesh) -> Vec<f32> {
+    par::map(mesh.n_vertices(), |v| {
+        mesh.corners(v)
+            .map(|c| {
+                let [v1, v2, v3] = mesh.face_positions(mesh.face(c));
+
+                // A área do triângulo é metade da norma do produto vetorial
+                // de duas de suas arestas.
+                let area = 0.5 * (v2 - v1).cross(v3 - v1).mag();
+
+                area / 3.0
+            })
+            .fold(0.0, |acc, a| acc + a)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mesh::Mesh, surfaces};
+
+    /// Maior norma entre as colunas de `m`.
+    fn norm(m: &Mat2) -> f32 {
+        m.cols[0].mag().max(m.cols[1].mag())
+    }
+
+    /// Malha plana de triângulos equiláteros de lado 1, com `n` por `n`
+    /// losangos. Os vizinhos de cada vértice estão a distância 1, e todos
+    /// os outros vértices a pelo menos $\sqrt 3$.
+    fn triangular_grid(n: u32) -> HalfEdgeMesh {
+        let h = 3.0f32.sqrt() / 2.0;
+        let idx = |i: u32, j: u32| j * (n + 1) + i;
+
+        let positions = (0..=n)
+            .flat_map(|j| (0..=n).map(move |i| Vec3::new(i as f32 + 0.5 * j as f32, j as f32 * h, 0.0)))
+            .collect();
+        let triangles = (0..n)
+            .flat_map(|j| (0..n).map(move |i| (i, j)))
+            .flat_map(|(i, j)| {
+                [
+                    [idx(i, j), idx(i + 1, j), idx(i, j + 1)],
+                    [idx(i + 1, j), idx(i + 1, j + 1), idx(i, j + 1)],
+                ]
+            })
+            .collect();
+
+        HalfEdgeMesh::new(&Mesh::new(positions, triangles)).unwrap()
+    }
+
+    #[test]
+    fn one_ring_neighborhoods() {
+        let mesh = triangular_grid(4);
+        let nbhds = compute_neighborhoods(&mesh);
+
+        // Um anel, e as bolas de raio logo acima do comprimento das arestas,
+        // são a vizinhança imediata.
+        assert_eq!(compute_k_ring_neighborhoods(&nbhds, 1), nbhds);
+        assert_eq!(compute_ball_neighborhoods(&mesh, &nbhds, 1.01), nbhds);
+        assert_eq!(compute_geodesic_neighborhoods(&mesh, &nbhds, 1.01), nbhds);
+
+        // Dois anéis já têm os vizinhos dos vizinhos.
+        let two_rings = compute_k_ring_neighborhoods(&nbhds, 2);
+        for (i, (ring, nbhd)) in two_rings.iter().zip(&nbhds).enumerate() {
+            assert!(ring.is_superset(nbhd) && ring.len() > nbhd.len(), "vertex {}", i);
+            assert!(!ring.contains(&(i as u32)), "vertex {}", i);
+        }
+    }
+
+    #[test]
+    fn geodesic_neighborhoods_follow_the_surface() {
+        // Uma faixa dobrada: o perfil vai de (0, 0) até (1, 0) e volta até
+        // (0, 0.2), e é repetido ao longo de y. O terceiro ponto do perfil
+        // fica a 0.2 do primeiro no espaço, mas a mais de 2 pela superfície.
+        let profile = [Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.2)];
+        let idx = |col: u32, k: u32| col * 3 + k;
+
+        let positions = (0..4)
+            .flat_map(|col| profile.map(|p| p + Vec3::new(0.0, col as f32, 0.0)))
+            .collect();
+        let triangles = (0..3)
+            .flat_map(|col| (0..2).map(move |k| (col, k)))
+            .flat_map(|(col, k)| {
+                [
+                    [idx(col, k), idx(col, k + 1), idx(col + 1, k + 1)],
+                    [idx(col, k), idx(col + 1, k + 1), idx(col + 1, k)],
+                ]
+            })
+            .collect();
+        let mesh = HalfEdgeMesh::new(&Mesh::new(positions, triangles)).unwrap();
+        let nbhds = compute_neighborhoods(&mesh);
+
+        let (center, folded) = (idx(1, 0), idx(1, 2));
+        let r = 1.05;
+        assert!((mesh.position(folded as usize) - mesh.position(center as usize)).mag() < r);
+
+        // A bola alcança o outro lado da dobra pela ponta, que está dentro
+        // dela; o caminho pela superfície é longo demais.
+        let ball = compute_ball_neighborhoods(&mesh, &nbhds, r);
+        let geodesic = compute_geodesic_neighborhoods(&mesh, &nbhds, r);
+
+        assert!(ball[center as usize].contains(&folded));
+        assert!(!geodesic[center as usize].contains(&folded));
+        assert!(geodesic[center as usize].is_superset(&nbhds[center as usize]));
+    }
+
+    /// Ângulo, em graus, entre a normal `n` e a normal exata no ponto `p` de
+    /// uma esfera centrada na origem.
+    fn sphere_normal_error(n: Vec3, p: Vec3) -> f32 {
+        n.dot(p.normalized()).clamp(-1.0, 1.0).acos().to_degrees()
+    }
+
+    /// Maior [`sphere_normal_error`] entre os vértices.
+    fn max_sphere_normal_error(mesh: &HalfEdgeMesh, normals: &[Vec3]) -> f32 {
+        normals
+            .iter()
+            .zip(mesh.positions())
+            .map(|(&n, &p)| sphere_normal_error(n, p))
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn weighted_normals_on_sphere() {
+        let mesh = HalfEdgeMesh::new(&surfaces::sphere(1.0, 24).mesh).unwrap();
+
+        // Os pesos de Max são exatos para vértices sobre uma esfera; os
+        // outros erram mais perto dos pólos, onde os triângulos são finos.
+        for (weighting, tolerance) in [
+            (NormalWeighting::Uniform, 3.0),
+            (NormalWeighting::Area, 3.0),
+            (NormalWeighting::Angle, 3.0),
+            (NormalWeighting::Max, 0.1),
+        ] {
+            let error = max_sphere_normal_error(&mesh, &compute_weighted_normals(&mesh, weighting));
+
+            assert!(error < tolerance, "{:?}: {} degrees", weighting, error);
+        }
+    }
+
+    #[test]
+    fn pca_normals_on_sphere() {
+        let mesh = HalfEdgeMesh::new(&surfaces::sphere(1.0, 24).mesh).unwrap();
+        let nbhds = compute_neighborhoods(&mesh);
+
+        let normals = compute_pca_normals(&mesh, &nbhds);
+
+        // O plano de quadrados mínimos inclina quando a vizinhança não é
+        // simétrica em volta do vértice. Isso acontece em todo vértice da
+        // esfera (as diagonais dos quadriláteros vão todas para o mesmo
+        // lado), e bem mais no anel em volta de cada pólo, que tem o pólo
+        // de um lado só e cinco vizinhos em vez de seis.
+        let error = max_sphere_normal_error(&mesh, &normals);
+        assert!(error < 8.0, "{} degrees", error);
+
+        let error = (0..mesh.n_vertices())
+            .filter(|&i| nbhds[i].len() == 6)
+            .map(|i| sphere_normal_error(normals[i], mesh.position(i)))
+            .fold(0.0, f32::max);
+        assert!(error < 3.0, "{} degrees", error);
+    }
+
+    #[test]
+    fn obj_without_normals_gets_outward_normals() {
+        let sphere = surfaces::sphere(1.0, 24).mesh;
+        let obj = tobj::Mesh {
+            positions: sphere.positions.iter().flat_map(|p| [p.x, p.y, p.z]).collect(),
+            indices: sphere.triangles.iter().flatten().copied().collect(),
+            ..Default::default()
+        };
+
+        let mesh = Mesh::try_from(&obj).unwrap();
+        assert_eq!(mesh.normals, None);
+        assert_eq!(mesh.corner_normals, None);
+
+        let mesh = HalfEdgeMesh::new(&mesh).unwrap();
+        assert!(!mesh.has_normals());
+
+        let error = max_sphere_normal_error(&mesh, &compute_avg_normals(&mesh));
+
+        assert!(error < 3.0, "{} degrees", error);
+    }
+
+    #[test]
+    fn principal_directions_of_known_shape_operators() {
+        // Uma base qualquer do plano tangente, com a normal em z.
+        let basis = Mat3::new(Vec3::unit_y(), -Vec3::unit_x(), Vec3::unit_z());
+        let world = |x: f32, y: f32| basis.cols[0] * x + basis.cols[1] * y;
+
+        // As direções só estão definidas a menos do sinal.
+        let assert_parallel = |d: Vec3, expected: Vec3| {
+            assert!((d.mag() - 1.0).abs() < 1e-6 && d.dot(expected).abs() > 1.0 - 1e-6, "{:?} != {:?}", d, expected);
+        };
+
+        let (sin, cos) = std::f32::consts::FRAC_PI_6.sin_cos();
+        let rotated = {
+            // R diag(3, 1) R', com R a rotação de 30 graus.
+            let (a, b, c) = (3.0 * cos * cos + sin * sin, 2.0 * sin * cos, 3.0 * sin * sin + cos * cos);
+
+            Mat2::new(Vec2::new(a, b), Vec2::new(b, c))
+        };
+
+        let cases = [
+            (Mat2::new(Vec2::new(3.0, 0.0), Vec2::new(0.0, 1.0)), world(0.0, 1.0), world(1.0, 0.0)),
+            (Mat2::new(Vec2::new(1.0, 0.0), Vec2::new(0.0, 3.0)), world(1.0, 0.0), world(0.0, 1.0)),
+            (rotated, world(-sin, cos), world(cos, sin)),
+        ];
+
+        let shape_ops = cases.map(|(s, _, _)| s);
+        let principal = compute_principal_directions(&shape_ops, &[basis; 3]);
+
+        for (p, (_, d1, d2)) in principal.iter().zip(cases) {
+            assert!((p.k1 - 1.0).abs() < 1e-6 && (p.k2 - 3.0).abs() < 1e-6, "{:?}", p);
+            assert!((p.mean() - 2.0).abs() < 1e-6 && (p.gaussian() - 3.0).abs() < 1e-5, "{:?}", p);
+
+            assert_parallel(p.d1, d1);
+            assert_parallel(p.d2, d2);
+        }
+
+        // Num ponto umbílico qualquer direção serve, mas elas continuam
+        // unitárias e perpendiculares.
+        let umbilic = compute_principal_directions(&[Mat2::new(Vec2::new(2.0, 0.0), Vec2::new(0.0, 2.0))], &[basis]);
+        assert_eq!((umbilic[0].k1, umbilic[0].k2), (2.0, 2.0));
+        assert!(umbilic[0].d1.dot(umbilic[0].d2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn simd_matches_scalar() {
+        // Os cantos da sela têm só dois vizinhos, e um vértice a mais fica
+        // isolado, então também comparamos os vértices descartados. A última
+        // leva de oito vértices fica incompleta.
+        let mut surface = surfaces::hyperbolic_paraboloid(1.0, 2.0, 13).mesh;
+        surface.positions.push(Vec3::zero());
+        if let Some(normals) = &mut surface.normals {
+            normals.push(Vec3::unit_z());
+        }
+
+        let mesh = HalfEdgeMesh::new(&surface).unwrap();
+        assert_ne!(mesh.n_vertices() % 8, 0);
+
+        let nbhds = compute_neighborhoods(&mesh);
+        let normals = compute_avg_normals(&mesh);
+        let tangent_bases = compute_tangent_basis(&mesh, &nbhds, &normals);
+
+        let scalar = compute_shape_operator_checked(&mesh, &nbhds, &tangent_bases, FitWeighting::Uniform);
+        let simd = compute_shape_operator_simd(&mesh, &nbhds, &tangent_bases, FitWeighting::Uniform);
+
+        assert_eq!(scalar.len(), simd.len());
+        assert!(scalar.contains(&Err(VertexStatus::Isolated)));
+        assert!(scalar.contains(&Err(VertexStatus::TooFewNeighbours)));
+
+        // Numa malha bem comportada, as diferenças de arredondamento ficam
+        // bem abaixo de 1e-4 do tamanho do Shape Operator (em malhas com
+        // ajustes quase singulares, como `sphere.obj`, chegam a 4e-3).
+        const TOLERANCE: f32 = 1e-4;
+
+        for (i, (s, v)) in scalar.iter().zip(&simd).enumerate() {
+            match (s, v) {
+                (Ok(s), Ok(v)) => {
+                    let error = norm(&Mat2::new(s.cols[0] - v.cols[0], s.cols[1] - v.cols[1]));
+
+                    assert!(error <= TOLERANCE * norm(s), "vertex {}: {:?} != {:?}", i, s, v);
+                }
+                _ => assert_eq!(s, v, "vertex {}", i),
+            }
+        }
+    }
 }
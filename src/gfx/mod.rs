@@ -8,8 +8,13 @@
 //! Se tiver interesse sobre o que está aqui, fique a vontade em me
 //! escrever :)
 
-mod vertex_buffer;
+pub mod font;
+mod overlay;
 mod shader;
+mod texture;
+mod vertex_buffer;
 
-pub use vertex_buffer::*;
+pub use overlay::*;
 pub use shader::*;
+pub use texture::*;
+pub use vertex_buffer::*;
@@ -0,0 +1,87 @@
+//! # Sobreposição
+//!
+//! Um buffer de triângulos 2D desenhados por cima da cena, em coordenadas
+//! de pixels da janela (origem no canto superior esquerdo, y para baixo).
+//! É usado para a legenda das cores.
+//!
+//! Cada vértice carrega, além da posição, um valor: entre 0 e 1 ele é a
+//! coordenada na tabela de cores, e negativo ele pinta o vértice de branco
+//! (usado para o texto).
+//!
+//! Esse módulo não contém nada de especial, e só serve como utilidade
+//! para remover o boilerplate do código principal do projeto.
+
+use core::slice;
+
+use glow::{Context, HasContext};
+
+/// Valor dos vértices pintados de branco.
+pub const OVERLAY_WHITE: f32 = -1.0;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct OverlayVertex {
+    pub position: [f32; 2],
+    pub value: f32,
+}
+
+pub struct OverlayBuffer {
+    vao: glow::VertexArray,
+    vbo: glow::Buffer,
+    n_vertices: usize,
+}
+
+impl OverlayBuffer {
+    pub fn new(gl: &Context) -> OverlayBuffer {
+        unsafe {
+            let vao = gl.create_vertex_array().unwrap();
+            gl.bind_vertex_array(Some(vao));
+
+            let vbo = gl.create_buffer().unwrap();
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+
+            let stride = std::mem::size_of::<OverlayVertex>() as i32;
+
+            gl.enable_vertex_attrib_array(0);
+            gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, stride, 0);
+
+            gl.enable_vertex_attrib_array(1);
+            gl.vertex_attrib_pointer_f32(1, 1, glow::FLOAT, false, stride, 2 * std::mem::size_of::<f32>() as i32);
+
+            OverlayBuffer { vao, vbo, n_vertices: 0 }
+        }
+    }
+
+    /// Troca os triângulos do buffer.
+    pub fn set(&mut self, gl: &Context, vertices: &[OverlayVertex]) {
+        unsafe {
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+
+            // HACK
+            let data = slice::from_raw_parts(vertices.as_ptr() as *const u8, std::mem::size_of_val(vertices));
+
+            gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, data, glow::DYNAMIC_DRAW);
+        }
+
+        self.n_vertices = vertices.len();
+    }
+
+    pub fn draw(&self, gl: &Context) {
+        unsafe {
+            gl.bind_vertex_array(Some(self.vao));
+            gl.draw_arrays(glow::TRIANGLES, 0, self.n_vertices as i32);
+        }
+    }
+}
+
+/// Os dois triângulos do retângulo `[x0, y0, x1, y1]`, com os valores
+/// `top` em y0 e `bottom` em y1.
+pub fn rect_vertices(rect: [f32; 4], top: f32, bottom: f32) -> [OverlayVertex; 6] {
+    let [x0, y0, x1, y1] = rect;
+    let v = |x, y, value| OverlayVertex { position: [x, y], value };
+
+    [
+        v(x0, y0, top), v(x1, y0, top), v(x1, y1, bottom),
+        v(x0, y0, top), v(x1, y1, bottom), v(x0, y1, bottom),
+    ]
+}
@@ -85,7 +85,7 @@ impl Shader {
         unsafe {
             let loc = gl
                 .get_uniform_location(self.program, name)
-                .expect(&format!("Uniform {} not found", name));
+                .unwrap_or_else(|| panic!("Uniform {} not found", name));
 
             val.bind(gl, &loc);
         }
@@ -93,6 +93,9 @@ impl Shader {
 }
 
 pub trait Uniform {
+    /// # Safety
+    ///
+    /// O programa dono de `loc` precisa estar ligado (ver [`Shader::bind`]).
     unsafe fn bind(&self, gl: &glow::Context, loc: &glow::UniformLocation);
 }
 
@@ -101,3 +104,21 @@ impl Uniform for ultraviolet::Mat4 {
         gl.uniform_matrix_4_f32_slice(Some(loc), false, self.as_slice());
     }
 }
+
+impl Uniform for i32 {
+    unsafe fn bind(&self, gl: &glow::Context, loc: &glow::UniformLocation) {
+        gl.uniform_1_i32(Some(loc), *self);
+    }
+}
+
+impl Uniform for f32 {
+    unsafe fn bind(&self, gl: &glow::Context, loc: &glow::UniformLocation) {
+        gl.uniform_1_f32(Some(loc), *self);
+    }
+}
+
+impl Uniform for ultraviolet::Vec2 {
+    unsafe fn bind(&self, gl: &glow::Context, loc: &glow::UniformLocation) {
+        gl.uniform_2_f32(Some(loc), self.x, self.y);
+    }
+}
@@ -0,0 +1,51 @@
+//! # Textura
+//!
+//! Texturas 1D, usadas como tabelas de cores: o shader lê a cor de um
+//! valor $t \in [0, 1]$ direto da textura, e a placa de vídeo interpola
+//! entre as entradas vizinhas.
+//!
+//! Esse módulo não contém nada de especial, e só serve como utilidade
+//! para remover o boilerplate do código principal do projeto.
+
+use glow::{Context, HasContext};
+
+pub struct Texture1D {
+    texture: glow::Texture,
+}
+
+impl Texture1D {
+    pub fn from_rgb(gl: &Context, texels: &[[u8; 3]]) -> Texture1D {
+        unsafe {
+            let texture = gl.create_texture().unwrap();
+            gl.bind_texture(glow::TEXTURE_1D, Some(texture));
+
+            // As linhas de 3 bytes não estão alinhadas em 4.
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+
+            gl.tex_image_1d(
+                glow::TEXTURE_1D,
+                0,
+                glow::RGB8 as i32,
+                texels.len() as i32,
+                0,
+                glow::RGB,
+                glow::UNSIGNED_BYTE,
+                Some(texels.as_flattened()),
+            );
+
+            gl.tex_parameter_i32(glow::TEXTURE_1D, glow::TEXTURE_MIN_FILTER, glow::LINEAR as i32);
+            gl.tex_parameter_i32(glow::TEXTURE_1D, glow::TEXTURE_MAG_FILTER, glow::LINEAR as i32);
+            gl.tex_parameter_i32(glow::TEXTURE_1D, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
+
+            Texture1D { texture }
+        }
+    }
+
+    /// Liga a textura à unidade de textura `unit`.
+    pub fn bind(&self, gl: &Context, unit: u32) {
+        unsafe {
+            gl.active_texture(glow::TEXTURE0 + unit);
+            gl.bind_texture(glow::TEXTURE_1D, Some(self.texture));
+        }
+    }
+}
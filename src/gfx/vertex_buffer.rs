@@ -15,6 +15,7 @@ use ultraviolet::Vec3;
 pub struct VertexBuffer {
     vao: glow::VertexArray,
     n_vertices: usize,
+    scalar_vbos: Vec<glow::Buffer>,
 }
 
 impl VertexBuffer {
@@ -22,7 +23,7 @@ impl VertexBuffer {
         gl: &Context,
         vertices: Vec<Vec3>,
         normals: Option<Vec<Vec3>>,
-        gaussian_curvatures: Option<Vec<f32>>,
+        scalars: Vec<Vec<f32>>,
     ) -> VertexBuffer {
         unsafe {
             let vao = gl.create_vertex_array().unwrap();
@@ -58,22 +59,42 @@ impl VertexBuffer {
                 gl.memory_barrier(glow::ALL_BARRIER_BITS);
             };
 
-            if let Some(gaussian_curvatures) = gaussian_curvatures {
-                let vbo = gl.create_buffer().unwrap();
-                gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            // Cada grandeza escalar fica no seu próprio buffer; só um
+            // deles por vez fica ligado ao atributo 2 (ver `select_scalar`).
+            let scalar_vbos = scalars
+                .iter()
+                .map(|scalars| {
+                    let vbo = gl.create_buffer().unwrap();
+                    gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
 
-                // HACK
-                let data  = slice::from_raw_parts(gaussian_curvatures.as_ptr() as *const u8, gaussian_curvatures.len() * std::mem::size_of::<f32>());
+                    // HACK
+                    let data  = slice::from_raw_parts(scalars.as_ptr() as *const u8, scalars.len() * std::mem::size_of::<f32>());
 
-                gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, data, glow::STATIC_DRAW);
+                    gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, data, glow::STATIC_DRAW);
+
+                    vbo
+                })
+                .collect::<Vec<_>>();
+
+            if let Some(&vbo) = scalar_vbos.first() {
+                gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
 
                 gl.enable_vertex_attrib_array(2);
                 gl.vertex_attrib_pointer_f32(2, 1, glow::FLOAT, false, 0, 0);
 
                 gl.memory_barrier(glow::ALL_BARRIER_BITS);
-            };
+            }
 
-            VertexBuffer { vao, n_vertices: vertices.len() }
+            VertexBuffer { vao, n_vertices: vertices.len(), scalar_vbos }
+        }
+    }
+
+    /// Liga o `idx`-ésimo buffer de escalares ao atributo 2.
+    pub fn select_scalar(&self, gl: &Context, idx: usize) {
+        unsafe {
+            gl.bind_vertex_array(Some(self.vao));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.scalar_vbos[idx]));
+            gl.vertex_attrib_pointer_f32(2, 1, glow::FLOAT, false, 0, 0);
         }
     }
 
@@ -0,0 +1,500 @@
+//! # Malha de semi-arestas
+//!
+//! Um [`Mesh`] é só uma lista de triângulos: para descobrir quem é vizinho
+//! de quem, precisaríamos percorrer todas as faces de novo a cada pergunta.
+//! Esse módulo constrói, uma única vez, uma estrutura de *semi-arestas*
+//! (half-edges) que responde essas perguntas diretamente.
+//!
+//! Cada triângulo $(v_0, v_1, v_2)$ da face $f$ dá origem a três
+//! semi-arestas orientadas, $v_0 \to v_1$, $v_1 \to v_2$ e $v_2 \to v_0$,
+//! de índices $3f$, $3f + 1$ e $3f + 2$. Assim a face, a próxima e a
+//! anterior semi-aresta de cada uma saem de contas com o índice, e só
+//! precisamos guardar a *gêmea* de cada semi-aresta: a semi-aresta da face
+//! vizinha que percorre a mesma aresta no sentido contrário.
+//!
+//! Semi-arestas sem gêmea estão no *bordo* da malha. Arestas compartilhadas
+//! por duas faces com orientações incompatíveis também ficam sem gêmea, e
+//! são tratadas como bordo. Já arestas compartilhadas por mais de duas
+//! faces (malhas não-variedade) não têm uma gêmea natural, e a malha é
+//! rejeitada.
+//!
+//! Os índices das semi-arestas também servem como índices dos *cantos* dos
+//! triângulos: o canto $c$ é o canto da face no vértice de origem da
+//! semi-aresta $c$. É assim que guardamos as normais de cada canto, que no
+//! `.obj` podem ser diferentes para um mesmo vértice.
+
+use std::collections::BTreeSet;
+
+use ultraviolet::Vec3;
+
+use crate::{geom::GeomError, mesh::Mesh, par};
+
+/// Marca a ausência de semi-aresta (gêmea de uma semi-aresta do bordo, ou
+/// semi-aresta de saída de um vértice isolado).
+const NONE: u32 = u32::MAX;
+
+/// Malha triangular com conectividade de semi-arestas.
+#[derive(Clone, Debug)]
+pub struct HalfEdgeMesh {
+    positions: Vec<Vec3>,
+    /// Vértice de origem de cada semi-aresta. É a lista de índices das
+    /// faces, como no `.obj`.
+    origins: Vec<u32>,
+    /// Semi-aresta gêmea de cada semi-aresta, ou `NONE` no bordo.
+    twins: Vec<u32>,
+    /// Uma semi-aresta saindo de cada vértice, ou `NONE` se o vértice não
+    /// está em nenhuma face. Se o vértice está no bordo, é a semi-aresta
+    /// do bordo, para que [`HalfEdgeMesh::outgoing`] percorra o leque
+    /// inteiro.
+    outgoing: Vec<u32>,
+    /// Os cantos de cada vértice `v` são `vertex_corners[corner_offsets[v]..corner_offsets[v + 1]]`.
+    corner_offsets: Vec<u32>,
+    vertex_corners: Vec<u32>,
+    /// Normal de cada canto, se a malha tinha normais.
+    corner_normals: Option<Vec<Vec3>>,
+}
+
+impl HalfEdgeMesh {
+    /// Constrói a malha de semi-arestas.
+    ///
+    /// Falha se a malha for inválida (ver [`Mesh::validate`]) ou se alguma
+    /// aresta for compartilhada por mais de duas faces.
+    pub fn new(mesh: &Mesh) -> Result<HalfEdgeMesh, GeomError> {
+        mesh.validate()?;
+
+        // Ordenando as arestas (sem orientação), as repetidas ficam juntas.
+        let mut edges = mesh
+            .triangles
+            .iter()
+            .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+            .map(|(i, j)| [i.min(j), i.max(j)])
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+
+        if let Some(run) = edges.chunk_by(|a, b| a == b).find(|run| run.len() > 2) {
+            return Err(GeomError::NonManifoldEdge { edge: run[0], faces: run.len() });
+        }
+
+        let origins = mesh.triangles.iter().flatten().copied().collect::<Vec<_>>();
+
+        // Se as normais são por vértice, cada canto recebe a normal do seu
+        // vértice.
+        let corner_normals = match (&mesh.corner_normals, &mesh.normals) {
+            (Some(normals), _) => Some(normals.clone()),
+            (None, Some(normals)) => Some(origins.iter().map(|&v| normals[v as usize]).collect()),
+            (None, None) => None,
+        };
+
+        Ok(HalfEdgeMesh::build(mesh.positions.clone(), origins, corner_normals))
+    }
+
+    /// Monta a conectividade. `origins` já deve ter sido validado.
+    fn build(positions: Vec<Vec3>, origins: Vec<u32>, corner_normals: Option<Vec<Vec3>>) -> HalfEdgeMesh {
+        let n_halfedges = origins.len();
+
+        // Agrupa os cantos por vértice, em ordem crescente (uma ordenação
+        // por contagem).
+        let mut corner_offsets = vec![0u32; positions.len() + 1];
+        for &v in &origins {
+            corner_offsets[v as usize + 1] += 1;
+        }
+        for v in 0..positions.len() {
+            corner_offsets[v + 1] += corner_offsets[v];
+        }
+
+        let mut vertex_corners = vec![0u32; n_halfedges];
+        let mut fill = corner_offsets.clone();
+        for (h, &v) in origins.iter().enumerate() {
+            vertex_corners[fill[v as usize] as usize] = h as u32;
+            fill[v as usize] += 1;
+        }
+
+        let mut mesh = HalfEdgeMesh {
+            positions,
+            origins,
+            twins: Vec::new(),
+            outgoing: Vec::new(),
+            corner_offsets,
+            vertex_corners,
+            corner_normals,
+        };
+
+        // A gêmea de uma semi-aresta i -> j é a semi-aresta j -> i, que
+        // procuramos entre as que saem de j. Se alguma das duas arestas
+        // orientadas aparece mais de uma vez, as faces em volta da aresta
+        // têm orientações incompatíveis, e nenhuma delas ganha gêmea.
+        let unique = |from: usize, to: usize| {
+            let mut found = mesh.corners(from).filter(|&h| mesh.target(h) == to);
+
+            match (found.next(), found.next()) {
+                (Some(h), None) => Some(h),
+                _ => None,
+            }
+        };
+
+        let twins = par::map(n_halfedges, |h| {
+            let (i, j) = (mesh.origin(h), mesh.target(h));
+
+            unique(i, j)
+                .and(unique(j, i))
+                .map_or(NONE, |twin| twin as u32)
+        });
+        mesh.twins = twins;
+
+        // Escolhe a semi-aresta de saída de cada vértice, dando preferência
+        // às do bordo.
+        let outgoing = par::map(mesh.n_vertices(), |v| {
+            mesh.corners(v)
+                .filter(|&h| mesh.is_boundary_halfedge(h))
+                .last()
+                .or_else(|| mesh.corners(v).next())
+                .map_or(NONE, |h| h as u32)
+        });
+        mesh.outgoing = outgoing;
+
+        mesh
+    }
+
+    pub fn n_vertices(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn n_faces(&self) -> usize {
+        self.origins.len() / 3
+    }
+
+    pub fn n_halfedges(&self) -> usize {
+        self.origins.len()
+    }
+
+    /// Posições de todos os vértices.
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    pub fn position(&self, v: usize) -> Vec3 {
+        self.positions[v]
+    }
+
+    /// Índices dos vértices de todas as faces, três por face, na mesma
+    /// ordem de [`Mesh::triangles`].
+    pub fn indices(&self) -> &[u32] {
+        &self.origins
+    }
+
+    /// Os três vértices da face `f`, na ordem da face.
+    pub fn face_vertices(&self, f: usize) -> [usize; 3] {
+        [0, 1, 2].map(|k| self.origins[3*f + k] as usize)
+    }
+
+    /// As posições dos três vértices da face `f`.
+    pub fn face_positions(&self, f: usize) -> [Vec3; 3] {
+        self.face_vertices(f).map(|v| self.positions[v])
+    }
+
+    /// Itera sobre os vértices de cada face.
+    pub fn faces(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        (0..self.n_faces()).map(move |f| self.face_vertices(f))
+    }
+
+    /// A face que contém a semi-aresta `h`.
+    pub fn face(&self, h: usize) -> usize {
+        h / 3
+    }
+
+    /// A semi-aresta seguinte a `h` na sua face.
+    pub fn next(&self, h: usize) -> usize {
+        3*(h/3) + (h + 1) % 3
+    }
+
+    /// A semi-aresta anterior a `h` na sua face.
+    pub fn prev(&self, h: usize) -> usize {
+        3*(h/3) + (h + 2) % 3
+    }
+
+    /// A semi-aresta gêmea de `h`, ou `None` se `h` está no bordo.
+    pub fn twin(&self, h: usize) -> Option<usize> {
+        match self.twins[h] {
+            NONE => None,
+            twin => Some(twin as usize),
+        }
+    }
+
+    /// O vértice de onde `h` sai.
+    pub fn origin(&self, h: usize) -> usize {
+        self.origins[h] as usize
+    }
+
+    /// O vértice onde `h` chega.
+    pub fn target(&self, h: usize) -> usize {
+        self.origins[self.next(h)] as usize
+    }
+
+    /// O vértice da face de `h` que não está em `h`.
+    pub fn opposite_vertex(&self, h: usize) -> usize {
+        self.origins[self.prev(h)] as usize
+    }
+
+    /// O canto oposto ao canto `c`.
+    ///
+    /// O canto `c` enxerga, na sua face, a aresta `next(c)`. O canto oposto
+    /// é o canto da face vizinha que enxerga a mesma aresta. Não existe se
+    /// essa aresta está no bordo.
+    pub fn opposite_corner(&self, c: usize) -> Option<usize> {
+        self.twin(self.next(c)).map(|twin| self.prev(twin))
+    }
+
+    /// Normal do canto `c`, se a malha tem normais.
+    pub fn corner_normal(&self, c: usize) -> Option<Vec3> {
+        self.corner_normals.as_ref().map(|normals| normals[c])
+    }
+
+    /// Se a malha tem normais nos cantos.
+    pub fn has_normals(&self) -> bool {
+        self.corner_normals.is_some()
+    }
+
+    /// Se a semi-aresta `h` está no bordo da malha.
+    pub fn is_boundary_halfedge(&self, h: usize) -> bool {
+        self.twins[h] == NONE
+    }
+
+    /// Se o vértice `v` está no bordo da malha.
+    ///
+    /// Vértices isolados não estão no bordo.
+    pub fn is_boundary_vertex(&self, v: usize) -> bool {
+        match self.outgoing[v] {
+            NONE => false,
+            h => self.is_boundary_halfedge(h as usize),
+        }
+    }
+
+    /// Se o vértice `v` não está em nenhuma face.
+    pub fn is_isolated(&self, v: usize) -> bool {
+        self.outgoing[v] == NONE
+    }
+
+    /// Os cantos do vértice `v` (ou seja, as semi-arestas que saem dele),
+    /// em ordem crescente de índice.
+    ///
+    /// Ao contrário de [`HalfEdgeMesh::outgoing`], inclui todas as faces de
+    /// `v`, mesmo onde a malha não é uma variedade, mas não segue a ordem
+    /// das faces em volta do vértice. É o que usamos para acumular, vértice a
+    /// vértice, grandezas que vêm dos cantos.
+    pub fn corners(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        let range = self.corner_offsets[v] as usize..self.corner_offsets[v + 1] as usize;
+
+        self.vertex_corners[range].iter().map(|&h| h as usize)
+    }
+
+    /// Itera sobre as semi-arestas que saem de `v`, em ordem, girando no
+    /// sentido da orientação das faces.
+    ///
+    /// Se `v` está no bordo, a iteração começa na semi-aresta do bordo e
+    /// percorre o leque de faces até o outro lado do bordo. Se a malha não
+    /// é uma variedade em `v`, só um dos leques é percorrido.
+    pub fn outgoing(&self, v: usize) -> Outgoing<'_> {
+        let start = match self.outgoing[v] {
+            NONE => None,
+            h => Some(h as usize),
+        };
+
+        Outgoing { mesh: self, start, current: start }
+    }
+
+    /// As faces em volta de `v`, na ordem de [`HalfEdgeMesh::outgoing`].
+    pub fn vertex_faces(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.outgoing(v).map(|h| self.face(h))
+    }
+
+    /// Os vizinhos de `v`, em ordem (o "1-anel ordenado").
+    ///
+    /// Se `v` está no bordo, o primeiro e o último vizinhos são os
+    /// vizinhos ao longo do bordo.
+    pub fn one_ring(&self, v: usize) -> Vec<usize> {
+        let mut ring = Vec::new();
+        let mut last = None;
+
+        for h in self.outgoing(v) {
+            ring.push(self.target(h));
+            last = Some(h);
+        }
+
+        // No bordo, o leque não fecha: o último vizinho só aparece como
+        // origem da semi-aresta que chega em `v` pela última face.
+        if let Some(last) = last {
+            if self.is_boundary_halfedge(self.prev(last)) {
+                ring.push(self.opposite_vertex(last));
+            }
+        }
+
+        ring
+    }
+
+    /// As vizinhanças imediatas de todos os vértices.
+    ///
+    /// Ao contrário de [`HalfEdgeMesh::one_ring`], considera todas as faces
+    /// de cada vértice, mesmo onde a malha não é uma variedade, mas perde a
+    /// ordem dos vizinhos.
+    pub fn neighborhoods(&self) -> Vec<BTreeSet<u32>> {
+        // Os vizinhos de `v` em cada face são os outros dois vértices dela:
+        // o destino da semi-aresta que sai de `v`, e a origem da que chega.
+        par::map(self.n_vertices(), |v| {
+            self.corners(v)
+                .flat_map(|c| [self.target(c), self.opposite_vertex(c)])
+                .map(|nb| nb as u32)
+                .collect()
+        })
+    }
+
+    /// A característica de Euler $\chi = V - E + F$ da malha.
+    ///
+    /// Vértices isolados não são contados. Cada aresta interior tem duas
+    /// semi-arestas, e cada aresta do bordo só uma.
+    pub fn euler_characteristic(&self) -> i64 {
+        let v = (0..self.n_vertices()).filter(|&v| !self.is_isolated(v)).count();
+        let boundary = (0..self.n_halfedges()).filter(|&h| self.is_boundary_halfedge(h)).count();
+        let e = (self.n_halfedges() - boundary) / 2 + boundary;
+
+        v as i64 - e as i64 + self.n_faces() as i64
+    }
+
+    /// Os laços do bordo da malha, cada um como a sequência de vértices
+    /// que o percorre.
+    ///
+    /// Os laços seguem a orientação das semi-arestas do bordo, ou seja, a
+    /// das faces. Uma malha fechada não tem laços.
+    pub fn boundary_loops(&self) -> Vec<Vec<usize>> {
+        let mut visited = vec![false; self.n_halfedges()];
+        let mut loops = Vec::new();
+
+        for start in 0..self.n_halfedges() {
+            if visited[start] || !self.is_boundary_halfedge(start) {
+                continue;
+            }
+
+            let mut boundary_loop = Vec::new();
+            let mut h = start;
+
+            while !visited[h] {
+                visited[h] = true;
+                boundary_loop.push(self.origin(h));
+
+                // A próxima semi-aresta do bordo sai do destino de `h`.
+                // Giramos em volta dele, atravessando as faces, até
+                // encontrá-la.
+                let mut next = self.next(h);
+                while let Some(twin) = self.twin(next) {
+                    next = self.next(twin);
+                }
+
+                h = next;
+            }
+
+            loops.push(boundary_loop);
+        }
+
+        loops
+    }
+}
+
+/// Iterador sobre as semi-arestas que saem de um vértice.
+///
+/// Ver [`HalfEdgeMesh::outgoing`].
+pub struct Outgoing<'a> {
+    mesh: &'a HalfEdgeMesh,
+    start: Option<usize>,
+    current: Option<usize>,
+}
+
+impl Iterator for Outgoing<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let h = self.current?;
+
+        // A semi-aresta anterior a `h` chega no vértice; a gêmea dela sai
+        // dele, na face seguinte.
+        self.current = self
+            .mesh
+            .twin(self.mesh.prev(h))
+            .filter(|&next| Some(next) != self.start);
+
+        Some(h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn half_edge_mesh(positions: &[[f32; 3]], triangles: &[[u32; 3]]) -> Result<HalfEdgeMesh, GeomError> {
+        let positions = positions.iter().map(|&p| Vec3::from(p)).collect();
+
+        HalfEdgeMesh::new(&Mesh::new(positions, triangles.to_vec()))
+    }
+
+    #[test]
+    fn single_triangle() {
+        let mesh = half_edge_mesh(&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]], &[[0, 1, 2]]).unwrap();
+
+        assert_eq!(mesh.euler_characteristic(), 1);
+        assert_eq!(mesh.boundary_loops(), vec![vec![0, 1, 2]]);
+        assert!((0..3).all(|v| mesh.is_boundary_vertex(v)));
+    }
+
+    #[test]
+    fn closed_fan_ring_follows_face_orientation() {
+        // Um quadrado dividido em quatro triângulos em volta do centro, com
+        // as faces no sentido anti-horário.
+        let positions = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [0.0, -1.0, 0.0],
+        ];
+        let triangles = [[0, 1, 2], [0, 2, 3], [0, 3, 4], [0, 4, 1]];
+        let mesh = half_edge_mesh(&positions, &triangles).unwrap();
+
+        assert!(!mesh.is_boundary_vertex(0));
+
+        // O anel pode começar em qualquer vizinho, mas tem que girar no
+        // sentido das faces.
+        let ring = mesh.one_ring(0);
+        let start = ring.iter().position(|&v| v == 1).unwrap();
+        let rotated = (0..4).map(|k| ring[(start + k) % 4]).collect::<Vec<_>>();
+
+        assert_eq!(ring.len(), 4);
+        assert_eq!(rotated, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn tetrahedron_is_closed() {
+        let positions = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let triangles = [[0, 2, 1], [0, 1, 3], [1, 2, 3], [0, 3, 2]];
+        let mesh = half_edge_mesh(&positions, &triangles).unwrap();
+
+        assert_eq!(mesh.euler_characteristic(), 2);
+        assert!(mesh.boundary_loops().is_empty());
+        assert!((0..mesh.n_halfedges()).all(|h| mesh.twin(h).is_some()));
+    }
+
+    #[test]
+    fn non_manifold_edge_is_rejected() {
+        // Três triângulos pendurados na aresta entre 0 e 1.
+        let positions = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, -1.0, 0.0],
+        ];
+        let triangles = [[0, 1, 2], [1, 0, 3], [0, 1, 4]];
+
+        assert_eq!(
+            half_edge_mesh(&positions, &triangles).unwrap_err(),
+            GeomError::NonManifoldEdge { edge: [0, 1], faces: 3 },
+        );
+    }
+}
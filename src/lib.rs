@@ -0,0 +1,64 @@
+//! # gauss
+//!
+//! Biblioteca para calcular (aproximações da) geometria intrínseca de
+//! malhas triangulares.
+//!
+//! A parte interessante está no módulo [`geom`], que contém o cálculo
+//! das curvaturas pelo ajuste de parabolóides, feito sobre a malha de
+//! semi-arestas de [`halfedge`]. As malhas de entrada são descritas pelo
+//! tipo [`mesh::Mesh`], que não depende do formato do arquivo. O módulo
+//! [`discrete`] contém estimadores alternativos, baseados em operadores
+//! discretos, e o [`pipeline`] junta tudo numa única chamada. O módulo
+//! [`surfaces`] gera malhas de superfícies conhecidas, com as curvaturas
+//! exatas, para medir o erro dos estimadores, e o [`convergence`] usa essas
+//! malhas para ver como o erro cai com a resolução. O módulo [`double`]
+//! refaz os cálculos em precisão dupla, para malhas em que `f32` não basta.
+//! O visualizador (módulos `camera`, `colormap`, `gfx` e `viewer`) só é compilado com a feature
+//! `viewer`, que é a que puxa `sdl2` e `glow`. Quem só quer a matemática
+//! pode depender do crate com `default-features = false`.
+//!
+//! Com a feature `parallel`, os cálculos por vértice rodam em paralelo
+//! (usando o `rayon`), com os mesmos resultados da versão em série.
+
+// Usamos `+ 0` nos índices para alinhar visualmente os acessos às coordenadas.
+#![allow(clippy::identity_op)]
+
+pub mod convergence;
+pub mod discrete;
+pub mod double;
+pub mod export;
+pub mod geom;
+pub mod halfedge;
+pub mod mesh;
+mod par;
+pub mod pipeline;
+pub mod stats;
+pub mod surfaces;
+
+#[cfg(feature = "viewer")]
+pub mod camera;
+#[cfg(feature = "viewer")]
+pub mod colormap;
+#[cfg(feature = "viewer")]
+pub mod gfx;
+#[cfg(feature = "viewer")]
+pub mod viewer;
+
+/// Reexportamos o [`tobj`], já que [`load_obj`] devolve os modelos dele.
+/// Para os cálculos, eles são convertidos em [`mesh::Mesh`].
+pub use tobj;
+
+/// Carrega os modelos de um arquivo `.obj`.
+///
+/// Usa as mesmas opções de carregamento que o visualizador: as faces são
+/// trianguladas, e os índices de vértices e de normais são mantidos
+/// separados (`single_index = false`), que é o que a conversão para
+/// [`mesh::Mesh`] espera.
+pub fn load_obj<P: AsRef<std::path::Path>>(path: P) -> Result<Vec<tobj::Model>, tobj::LoadError> {
+    let mut load_opts = tobj::GPU_LOAD_OPTIONS;
+    load_opts.single_index = false;
+
+    let (models, _) = tobj::load_obj(path.as_ref(), &load_opts)?;
+
+    Ok(models)
+}
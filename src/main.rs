@@ -1,66 +1,51 @@
-use std::time::Instant;
-
-use glow::HasContext;
-use viewer::Viewer;
-
-mod gfx;
-mod viewer;
-mod geom;
-
-fn main() {
-    let sdl = sdl2::init().unwrap();
-    let video = sdl.video().unwrap();
-    let gl_attr = video.gl_attr();
-    gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
-    gl_attr.set_context_major_version(4);
-
-    let win = video
-        .window("Gauss", 1280, 720)
-        .opengl()
-        .build()
-        .unwrap();
-
-    let _gl_ctx = win.gl_create_context().unwrap();
-    let gl = unsafe { glow::Context::from_loader_function(|s| video.gl_get_proc_address(s) as *const _) };
-
-    unsafe {
-        gl.enable(glow::DEPTH_TEST);
-    }
-
-    //let shader = crate::gfx::Shader::new(
-    //    &gl,
-    //    include_str!("../res/shaders/simple.frag.glsl"),
-    //    include_str!("../res/shaders/simple.vert.glsl"),
-    //).unwrap();
-
-    let mut viewer = Viewer::new(&gl);
-
-    let mut last_frame = Instant::now();
-
-    let mut evt_loop = sdl.event_pump().unwrap();
-
-    'main: loop {
-        for evt in evt_loop.poll_iter() {
-            match evt {
-                sdl2::event::Event::Quit { .. } => { break 'main; }
-                _ => {  }
+use std::process::ExitCode;
+
+mod analyze;
+mod cli;
+mod convergence_cmd;
+mod export_cmd;
+#[cfg(feature = "viewer")]
+mod window;
+
+fn main() -> ExitCode {
+    let cli = cli::Cli::parse_checked();
+
+    match cli.command {
+        Some(cli::Command::Analyze(args)) => {
+            if analyze::run(&args) {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
             }
         }
-
-        let now = Instant::now();
-
-        let delta = now - last_frame;
-
-        viewer.update(delta);
-
-        unsafe {
-            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
-
-            viewer.render();
+        Some(cli::Command::Export(args)) => {
+            if export_cmd::run(&args) {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Some(cli::Command::Convergence(args)) => {
+            if convergence_cmd::run(&args) {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        #[cfg(feature = "viewer")]
+        Some(cli::Command::View(args)) => {
+            window::run(&args.viewer_options());
+            ExitCode::SUCCESS
+        }
+        #[cfg(feature = "viewer")]
+        None => {
+            window::run(&cli.view.viewer_options());
+            ExitCode::SUCCESS
+        }
+        #[cfg(not(feature = "viewer"))]
+        None => {
+            eprintln!("gauss was built without the `viewer` feature; use `gauss analyze`");
+            ExitCode::FAILURE
         }
-
-        win.gl_swap_window();
-
-        last_frame = now;
     }
 }
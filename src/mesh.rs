@@ -0,0 +1,228 @@
+//! # Malha triangular
+//!
+//! O tipo [`Mesh`] é a forma como o resto do crate recebe uma malha: uma
+//! lista de posições, uma lista de triângulos e, opcionalmente, normais.
+//! Ele não depende de nenhum formato de arquivo, então malhas carregadas
+//! por outras bibliotecas, ou geradas na memória, podem ser usadas
+//! diretamente.
+//!
+//! Para malhas carregadas pelo [`tobj`] (como as de [`crate::load_obj`]),
+//! há uma conversão com [`Mesh::try_from`].
+
+use ultraviolet::Vec3;
+
+use crate::geom::GeomError;
+
+/// Malha triangular.
+///
+/// As normais podem vir de duas formas: uma por vértice, ou uma por
+/// *canto* de cada triângulo (como no `.obj`, onde um mesmo vértice pode
+/// ter normais diferentes em cada face). Os cantos são numerados na ordem
+/// dos triângulos: o canto `k` do triângulo `f` tem índice `3*f + k`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    /// Posição de cada vértice.
+    pub positions: Vec<Vec3>,
+    /// Índices dos três vértices de cada triângulo. A orientação da face é
+    /// dada pela ordem dos vértices (regra da mão direita).
+    pub triangles: Vec<[u32; 3]>,
+    /// Normal de cada vértice, se houver.
+    pub normals: Option<Vec<Vec3>>,
+    /// Normal de cada canto, se houver. Se existirem as duas, as normais dos
+    /// cantos têm preferência.
+    pub corner_normals: Option<Vec<Vec3>>,
+}
+
+impl Mesh {
+    /// Cria uma malha sem normais.
+    pub fn new(positions: Vec<Vec3>, triangles: Vec<[u32; 3]>) -> Mesh {
+        Mesh {
+            positions,
+            triangles,
+            normals: None,
+            corner_normals: None,
+        }
+    }
+
+    /// Cria uma malha a partir de listas "achatadas", como as que as APIs
+    /// gráficas usam: três coordenadas por vértice em `positions`, e três
+    /// índices por triângulo em `indices`.
+    pub fn from_slices(positions: &[f32], indices: &[u32]) -> Result<Mesh, GeomError> {
+        if !positions.len().is_multiple_of(3) {
+            return Err(GeomError::MalformedPositions { len: positions.len() });
+        }
+
+        if !indices.len().is_multiple_of(3) {
+            return Err(GeomError::NotTriangulated { indices: indices.len() });
+        }
+
+        let mesh = Mesh::new(
+            positions.chunks_exact(3).map(|p| Vec3::new(p[0], p[1], p[2])).collect(),
+            indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect(),
+        );
+
+        mesh.validate()?;
+
+        Ok(mesh)
+    }
+
+    pub fn n_vertices(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn n_faces(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Se a malha tem normais, por vértice ou por canto.
+    pub fn has_normals(&self) -> bool {
+        self.normals.is_some() || self.corner_normals.is_some()
+    }
+
+    /// Verifica se a malha pode ser usada nos cálculos: todos os índices
+    /// devem referenciar vértices existentes, e as normais devem ter o
+    /// tamanho certo.
+    pub fn validate(&self) -> Result<(), GeomError> {
+        let n_vertices = self.n_vertices();
+
+        for (face, idxs) in self.triangles.iter().enumerate() {
+            if let Some(&index) = idxs.iter().find(|&&idx| idx as usize >= n_vertices) {
+                return Err(GeomError::IndexOutOfBounds { face, index, vertices: n_vertices });
+            }
+        }
+
+        if let Some(normals) = &self.normals {
+            if normals.len() != n_vertices {
+                return Err(GeomError::AttributeLength {
+                    attribute: "normals",
+                    expected: n_vertices,
+                    found: normals.len(),
+                });
+            }
+        }
+
+        if let Some(normals) = &self.corner_normals {
+            if normals.len() != 3 * self.n_faces() {
+                return Err(GeomError::AttributeLength {
+                    attribute: "corner normals",
+                    expected: 3 * self.n_faces(),
+                    found: normals.len(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Converte uma malha carregada pelo [`tobj`].
+///
+/// As faces devem estar trianguladas. As normais são lidas por canto, e
+/// por isso a malha deve ter sido carregada com `single_index = false` (como
+/// faz [`crate::load_obj`]); faces sem normais fazem a malha inteira ficar
+/// sem normais.
+impl TryFrom<&tobj::Mesh> for Mesh {
+    type Error = GeomError;
+
+    fn try_from(mesh: &tobj::Mesh) -> Result<Mesh, GeomError> {
+        let mut ret = Mesh::from_slices(&mesh.positions, &mesh.indices)?;
+
+        let has_normals = !mesh.normals.is_empty() && mesh.normal_indices.len() == mesh.indices.len();
+
+        if has_normals {
+            let normals = mesh
+                .normal_indices
+                .iter()
+                .enumerate()
+                .map(|(corner, &index)| {
+                    let idx = index as usize;
+
+                    mesh.normals
+                        .get(3*idx..3*idx + 3)
+                        .map(|n| Vec3::new(n[0], n[1], n[2]))
+                        .ok_or(GeomError::NormalIndexOutOfBounds {
+                            corner,
+                            index,
+                            normals: mesh.normals.len() / 3,
+                        })
+                })
+                .collect::<Result<_, _>>()?;
+
+            ret.corner_normals = Some(normals);
+        }
+
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Um triângulo com uma normal por canto, no formato do [`tobj`].
+    fn obj_triangle() -> tobj::Mesh {
+        tobj::Mesh {
+            positions: vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+            indices: vec![0, 1, 2],
+            normals: vec![0.0, 0.0, 1.0],
+            normal_indices: vec![0, 0, 0],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn valid_mesh() {
+        let mesh = Mesh::try_from(&obj_triangle()).unwrap();
+
+        assert_eq!(mesh.n_vertices(), 3);
+        assert_eq!(mesh.triangles, vec![[0, 1, 2]]);
+        assert_eq!(mesh.corner_normals, Some(vec![Vec3::unit_z(); 3]));
+        assert_eq!(mesh.validate(), Ok(()));
+    }
+
+    #[test]
+    fn malformed_positions() {
+        assert_eq!(
+            Mesh::from_slices(&[0.0; 10], &[0, 1, 2]),
+            Err(GeomError::MalformedPositions { len: 10 }),
+        );
+    }
+
+    #[test]
+    fn not_triangulated() {
+        assert_eq!(
+            Mesh::from_slices(&[0.0; 12], &[0, 1, 2, 3]),
+            Err(GeomError::NotTriangulated { indices: 4 }),
+        );
+    }
+
+    #[test]
+    fn index_out_of_bounds() {
+        assert_eq!(
+            Mesh::from_slices(&[0.0; 9], &[0, 1, 2, 2, 1, 3]),
+            Err(GeomError::IndexOutOfBounds { face: 1, index: 3, vertices: 3 }),
+        );
+    }
+
+    #[test]
+    fn normal_index_out_of_bounds() {
+        let mut obj = obj_triangle();
+        obj.normal_indices = vec![0, 1, 0];
+
+        assert_eq!(
+            Mesh::try_from(&obj),
+            Err(GeomError::NormalIndexOutOfBounds { corner: 1, index: 1, normals: 1 }),
+        );
+    }
+
+    #[test]
+    fn attribute_length() {
+        let mut mesh = Mesh::from_slices(&[0.0; 9], &[0, 1, 2]).unwrap();
+        mesh.normals = Some(vec![Vec3::unit_z(); 2]);
+
+        assert_eq!(
+            mesh.validate(),
+            Err(GeomError::AttributeLength { attribute: "normals", expected: 3, found: 2 }),
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! # Paralelismo
+//!
+//! Quase todos os cálculos do crate são laços independentes sobre os
+//! vértices (ou faces, ou semi-arestas) da malha. As funções desse módulo
+//! rodam esses laços em paralelo, com o [`rayon`], quando a feature
+//! `parallel` está ligada, e em série caso contrário.
+//!
+//! Cada elemento é calculado sozinho, sem depender da ordem em que os
+//! outros foram calculados, então o resultado é exatamente o mesmo nos
+//! dois casos.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Calcula `f(i)` para cada `i` em `0..n`, e junta os resultados em ordem.
+#[cfg(feature = "parallel")]
+pub(crate) fn map<T, F>(n: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync + Send,
+{
+    (0..n).into_par_iter().map(f).collect()
+}
+
+/// Calcula `f(i)` para cada `i` em `0..n`, e junta os resultados em ordem.
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn map<T, F>(n: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync + Send,
+{
+    (0..n).map(f).collect()
+}
+
+/// Chama `f(i, &mut items[i])` para cada elemento de `items`.
+#[cfg(feature = "parallel")]
+pub(crate) fn for_each_mut<T, F>(items: &mut [T], f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut T) + Sync + Send,
+{
+    items.par_iter_mut().enumerate().for_each(|(i, item)| f(i, item));
+}
+
+/// Chama `f(i, &mut items[i])` para cada elemento de `items`.
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn for_each_mut<T, F>(items: &mut [T], f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut T) + Sync + Send,
+{
+    items.iter_mut().enumerate().for_each(|(i, item)| f(i, item));
+}
@@ -0,0 +1,422 @@
+//! # Pipeline
+//!
+//! Junta os cálculos de [`crate::geom`] e [`crate::discrete`] numa única
+//! chamada, devolvendo todas as grandezas de cada vértice. É o que o
+//! visualizador, o `gauss analyze` e o `gauss export` usam.
+
+use std::collections::BTreeSet;
+
+use ultraviolet::{DMat2, DVec2, Mat2, Vec2, Vec3};
+
+use crate::{discrete, double, geom, halfedge::HalfEdgeMesh, mesh::Mesh};
+
+/// Qual estimador usar para a curvatura gaussiana.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GaussianEstimator {
+    /// Determinante do Shape Operator obtido pelo ajuste do parabolóide
+    /// (ver [`geom::compute_shape_operator`]).
+    #[default]
+    Paraboloid,
+    /// Defeito angular dividido pela área de Voronoi mista
+    /// (ver [`discrete::compute_angle_defect_curvatures`]).
+    AngleDefect,
+}
+
+/// Qual estimador usar para a curvatura média.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeanEstimator {
+    /// Traço do Shape Operator obtido pelo ajuste do parabolóide
+    /// (ver [`geom::compute_curvatures`]).
+    #[default]
+    Paraboloid,
+    /// Fórmula das cotangentes para o operador de Laplace-Beltrami
+    /// (ver [`discrete::compute_cotangent_mean_curvatures`]).
+    Cotangent,
+}
+
+/// De onde tirar as normais dos vértices, que determinam os planos
+/// tangentes usados no ajuste do parabolóide.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NormalSource {
+    /// Média das normais do arquivo `.obj` (ver [`geom::compute_avg_normals`]).
+    /// Se o arquivo não tem normais, elas são calculadas a partir das faces.
+    #[default]
+    File,
+    /// Média ponderada das normais das faces
+    /// (ver [`geom::compute_weighted_normals`]).
+    Geometry(geom::NormalWeighting),
+    /// Análise de componentes principais da vizinhança imediata
+    /// (ver [`geom::compute_pca_normals`]).
+    Pca,
+}
+
+/// O que fazer com os vértices do bordo de malhas abertas.
+///
+/// Os estimadores discretos não estão definidos no bordo, e sempre deixam
+/// esses vértices com `NaN` (ver [`discrete`]). Essas opções afetam o
+/// ajuste do parabolóide.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoundaryHandling {
+    /// Ajusta o parabolóide como nos vértices interiores, e só marca os
+    /// vértices em [`VertexAttributes::boundary`].
+    #[default]
+    Flag,
+    /// Ajusta o parabolóide com uma vizinhança maior, do lado de dentro
+    /// do bordo (ver [`geom::widen_boundary_neighborhoods`]).
+    OneSided,
+    /// Deixa as curvaturas dos vértices do bordo como `NaN`, com a situação
+    /// [`geom::VertexStatus::Boundary`], para que fiquem fora das
+    /// estatísticas.
+    Exclude,
+}
+
+/// Opções do pipeline.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PipelineOptions {
+    pub gaussian: GaussianEstimator,
+    pub mean: MeanEstimator,
+    /// Ponderação dos vizinhos no ajuste do parabolóide.
+    pub fit_weighting: geom::FitWeighting,
+    /// Como resolver os ajustes do parabolóide.
+    pub solver: geom::FitSolver,
+    /// Precisão das contas. Em precisão dupla, os ajustes são sempre
+    /// resolvidos um vértice de cada vez e `solver` não é usado (a linha de
+    /// comando recusa `--precision double --solver simd`).
+    pub precision: geom::Precision,
+    /// Vizinhança usada no ajuste do parabolóide.
+    pub neighborhood: geom::Neighborhood,
+    /// Normais dos vértices.
+    pub normals: NormalSource,
+    /// Tratamento dos vértices do bordo.
+    pub boundary: BoundaryHandling,
+}
+
+/// Grandezas calculadas para cada vértice da malha.
+///
+/// Todos os vetores são indexados pelo índice do vértice na malha.
+#[derive(Clone, Debug)]
+pub struct VertexAttributes {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    /// Área associada a cada vértice, consistente com o estimador de K
+    /// escolhido: área de Voronoi mista para o defeito angular, e área
+    /// baricêntrica para o parabolóide.
+    pub areas: Vec<f32>,
+    /// Curvatura gaussiana K.
+    pub gaussian: Vec<f32>,
+    /// Curvatura média H.
+    pub mean: Vec<f32>,
+    /// Menor curvatura principal.
+    pub k1: Vec<f32>,
+    /// Maior curvatura principal.
+    pub k2: Vec<f32>,
+    /// Índice de forma, a partir de `k1` e `k2` (ver [`geom::shape_index`]).
+    pub shape_index: Vec<f32>,
+    /// Resíduo do ajuste do parabolóide (ver [`geom::compute_fit_residuals`]).
+    pub fit_residual: Vec<f32>,
+    /// Direção principal associada a `k1`.
+    pub d1: Vec<Vec3>,
+    /// Direção principal associada a `k2`.
+    pub d2: Vec<Vec3>,
+    /// Situação do ajuste do parabolóide em cada vértice. Onde ela não é
+    /// [`geom::VertexStatus::Ok`], as grandezas que dependem do ajuste são
+    /// `NaN`.
+    pub status: Vec<geom::VertexStatus>,
+    /// Se cada vértice está no bordo da malha.
+    pub boundary: Vec<bool>,
+}
+
+impl VertexAttributes {
+    /// Roda os cálculos sobre a malha e junta os resultados.
+    ///
+    /// Falha apenas se a malha for inválida (ver [`Mesh::validate`]).
+    /// Vértices degenerados não interrompem o cálculo: eles são marcados
+    /// em [`VertexAttributes::status`].
+    pub fn compute(mesh: &Mesh, opts: &PipelineOptions) -> Result<VertexAttributes, geom::GeomError> {
+        let mesh = HalfEdgeMesh::new(mesh)?;
+
+        Ok(VertexAttributes::compute_on(&mesh, opts))
+    }
+
+    /// Igual a [`VertexAttributes::compute`], para uma malha que já foi
+    /// convertida em semi-arestas.
+    pub fn compute_on(mesh: &HalfEdgeMesh, opts: &PipelineOptions) -> VertexAttributes {
+        let nbhds = geom::compute_neighborhoods(mesh);
+        let mut fit_nbhds = match opts.neighborhood {
+            geom::Neighborhood::Ring(1) => nbhds.clone(),
+            kind => geom::compute_neighborhoods_with(mesh, &nbhds, kind),
+        };
+        if opts.boundary == BoundaryHandling::OneSided {
+            geom::widen_boundary_neighborhoods(mesh, &mut fit_nbhds);
+        }
+
+        let estimates = match opts.precision {
+            geom::Precision::Single => Estimates::single(mesh, &nbhds, &fit_nbhds, opts),
+            geom::Precision::Double => Estimates::double(mesh, &nbhds, &fit_nbhds, opts),
+        };
+        let principal = &estimates.principal;
+
+        let positions = mesh.positions().to_vec();
+        let boundary = (0..mesh.n_vertices()).map(|v| mesh.is_boundary_vertex(v)).collect();
+
+        let mut attrs = VertexAttributes {
+            positions,
+            normals: estimates.normals,
+            areas: estimates.areas,
+            gaussian: estimates.gaussian,
+            mean: estimates.mean,
+            k1: principal.iter().map(|p| p.k1).collect(),
+            k2: principal.iter().map(|p| p.k2).collect(),
+            shape_index: principal.iter().map(|p| p.shape_index()).collect(),
+            fit_residual: estimates.fit_residual,
+            d1: principal.iter().map(|p| p.d1).collect(),
+            d2: principal.iter().map(|p| p.d2).collect(),
+            status: estimates.status,
+            boundary,
+        };
+
+        if opts.boundary == BoundaryHandling::Exclude {
+            attrs.exclude_boundary();
+        }
+
+        attrs
+    }
+
+    /// Apaga as curvaturas dos vértices do bordo.
+    fn exclude_boundary(&mut self) {
+        let nan = Vec3::broadcast(f32::NAN);
+
+        for i in (0..self.n_vertices()).filter(|&i| self.boundary[i]) {
+            self.gaussian[i] = f32::NAN;
+            self.mean[i] = f32::NAN;
+            self.k1[i] = f32::NAN;
+            self.k2[i] = f32::NAN;
+            self.shape_index[i] = f32::NAN;
+            self.fit_residual[i] = f32::NAN;
+            self.d1[i] = nan;
+            self.d2[i] = nan;
+
+            if self.status[i].is_ok() {
+                self.status[i] = geom::VertexStatus::Boundary;
+            }
+        }
+    }
+
+    /// Número de vértices em que o ajuste do parabolóide falhou.
+    pub fn n_degenerate(&self) -> usize {
+        self.status.iter().filter(|s| s.is_degenerate()).count()
+    }
+
+    /// Número de vértices no bordo da malha.
+    pub fn n_boundary(&self) -> usize {
+        self.boundary.iter().filter(|&&b| b).count()
+    }
+
+    /// Número de vértices da malha.
+    pub fn n_vertices(&self) -> usize {
+        self.positions.len()
+    }
+}
+
+/// As grandezas de [`VertexAttributes`] que dependem da precisão das
+/// contas (ver [`geom::Precision`]).
+struct Estimates {
+    normals: Vec<Vec3>,
+    areas: Vec<f32>,
+    gaussian: Vec<f32>,
+    mean: Vec<f32>,
+    principal: Vec<geom::PrincipalCurvatures>,
+    fit_residual: Vec<f32>,
+    status: Vec<geom::VertexStatus>,
+}
+
+impl Estimates {
+    /// Todas as etapas em `f32`, com as funções de [`geom`] e [`discrete`].
+    fn single(
+        mesh: &HalfEdgeMesh,
+        nbhds: &[BTreeSet<u32>],
+        fit_nbhds: &[BTreeSet<u32>],
+        opts: &PipelineOptions,
+    ) -> Estimates {
+        let normals = match opts.normals {
+            NormalSource::File => geom::compute_avg_normals(mesh),
+            NormalSource::Geometry(weighting) => geom::compute_weighted_normals(mesh, weighting),
+            NormalSource::Pca => geom::compute_pca_normals(mesh, nbhds),
+        };
+        let tangent_basii = geom::compute_tangent_basis(mesh, nbhds, &normals);
+        let fits = match opts.solver {
+            geom::FitSolver::Scalar => {
+                geom::compute_shape_operator_checked(mesh, fit_nbhds, &tangent_basii, opts.fit_weighting)
+            }
+            geom::FitSolver::Simd => {
+                geom::compute_shape_operator_simd(mesh, fit_nbhds, &tangent_basii, opts.fit_weighting)
+            }
+        };
+
+        let nan = Mat2::new(Vec2::broadcast(f32::NAN), Vec2::broadcast(f32::NAN));
+        let (shape_ops, status): (Vec<_>, Vec<_>) = fits
+            .into_iter()
+            .map(|fit| match fit {
+                Ok(shape_op) => (shape_op, geom::VertexStatus::Ok),
+                Err(status) => (nan, status),
+            })
+            .unzip();
+
+        let fit_residual =
+            geom::compute_fit_residuals(mesh, fit_nbhds, &tangent_basii, &shape_ops, opts.fit_weighting);
+
+        let (paraboloid_k, paraboloid_h) = geom::compute_curvatures(&shape_ops).into_iter().unzip();
+        let principal = geom::compute_principal_directions(&shape_ops, &tangent_basii);
+
+        let (gaussian, areas) = match opts.gaussian {
+            GaussianEstimator::Paraboloid => (paraboloid_k, geom::compute_vertex_areas(mesh)),
+            GaussianEstimator::AngleDefect => (
+                discrete::compute_angle_defect_curvatures(mesh),
+                discrete::compute_mixed_areas(mesh),
+            ),
+        };
+
+        let mean = match opts.mean {
+            MeanEstimator::Paraboloid => paraboloid_h,
+            MeanEstimator::Cotangent => discrete::compute_cotangent_mean_curvatures(mesh)
+                .into_iter()
+                .map(|(h, _)| h)
+                .collect(),
+        };
+
+        Estimates { normals, areas, gaussian, mean, principal, fit_residual, status }
+    }
+
+    /// Todas as etapas em `f64`, com as funções de [`double`]. Só os
+    /// resultados voltam para `f32`.
+    ///
+    /// Os ajustes são sempre resolvidos um vértice de cada vez, então
+    /// `opts.solver` não é usado.
+    fn double(
+        mesh: &HalfEdgeMesh,
+        nbhds: &[BTreeSet<u32>],
+        fit_nbhds: &[BTreeSet<u32>],
+        opts: &PipelineOptions,
+    ) -> Estimates {
+        fn to_f32(values: Vec<f64>) -> Vec<f32> {
+            values.into_iter().map(|x| x as f32).collect()
+        }
+
+        let normals = match opts.normals {
+            NormalSource::File => double::compute_avg_normals(mesh),
+            NormalSource::Geometry(weighting) => double::compute_weighted_normals(mesh, weighting),
+            NormalSource::Pca => double::compute_pca_normals(mesh, nbhds),
+        };
+        let tangent_basii = double::compute_tangent_basis(mesh, nbhds, &normals);
+        let fits = double::compute_shape_operator(mesh, fit_nbhds, &tangent_basii, opts.fit_weighting);
+
+        let nan = DMat2::new(DVec2::broadcast(f64::NAN), DVec2::broadcast(f64::NAN));
+        let (shape_ops, status): (Vec<_>, Vec<_>) = fits
+            .into_iter()
+            .map(|fit| match fit {
+                Ok(shape_op) => (shape_op, geom::VertexStatus::Ok),
+                Err(status) => (nan, status),
+            })
+            .unzip();
+
+        let fit_residual =
+            double::compute_fit_residuals(mesh, fit_nbhds, &tangent_basii, &shape_ops, opts.fit_weighting);
+
+        let (paraboloid_k, paraboloid_h) = double::compute_curvatures(&shape_ops).into_iter().unzip();
+        let principal = double::compute_principal_directions(&shape_ops, &tangent_basii);
+
+        let (gaussian, areas) = match opts.gaussian {
+            GaussianEstimator::Paraboloid => (paraboloid_k, double::compute_vertex_areas(mesh)),
+            GaussianEstimator::AngleDefect => (
+                double::compute_angle_defect_curvatures(mesh),
+                double::compute_mixed_areas(mesh),
+            ),
+        };
+
+        let mean = match opts.mean {
+            MeanEstimator::Paraboloid => paraboloid_h,
+            MeanEstimator::Cotangent => double::compute_cotangent_mean_curvatures(mesh)
+                .into_iter()
+                .map(|(h, _)| h)
+                .collect(),
+        };
+
+        Estimates {
+            normals: normals.into_iter().map(double::to_f32).collect(),
+            areas: to_f32(areas),
+            gaussian: to_f32(gaussian),
+            mean: to_f32(mean),
+            principal: principal.iter().map(|p| p.to_f32()).collect(),
+            fit_residual: to_f32(fit_residual),
+            status,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use super::*;
+    use crate::surfaces;
+
+    fn bits(values: &[f32]) -> Vec<u32> {
+        values.iter().map(|x| x.to_bits()).collect()
+    }
+
+    fn vec_bits(values: &[Vec3]) -> Vec<[u32; 3]> {
+        values.iter().map(|v| [v.x.to_bits(), v.y.to_bits(), v.z.to_bits()]).collect()
+    }
+
+    /// Compara os atributos bit a bit, para que os `NaN` também contem.
+    fn assert_identical(a: &VertexAttributes, b: &VertexAttributes) {
+        assert_eq!(vec_bits(&a.positions), vec_bits(&b.positions));
+        assert_eq!(vec_bits(&a.normals), vec_bits(&b.normals));
+        assert_eq!(bits(&a.areas), bits(&b.areas));
+        assert_eq!(bits(&a.gaussian), bits(&b.gaussian));
+        assert_eq!(bits(&a.mean), bits(&b.mean));
+        assert_eq!(bits(&a.k1), bits(&b.k1));
+        assert_eq!(bits(&a.k2), bits(&b.k2));
+        assert_eq!(bits(&a.shape_index), bits(&b.shape_index));
+        assert_eq!(bits(&a.fit_residual), bits(&b.fit_residual));
+        assert_eq!(vec_bits(&a.d1), vec_bits(&b.d1));
+        assert_eq!(vec_bits(&a.d2), vec_bits(&b.d2));
+        assert_eq!(a.status, b.status);
+        assert_eq!(a.boundary, b.boundary);
+    }
+
+    #[test]
+    fn parallel_matches_serial() {
+        // Uma malha aberta, para passar também pelo bordo.
+        let mesh = HalfEdgeMesh::new(&surfaces::hyperbolic_paraboloid(1.0, 2.0, 24).mesh).unwrap();
+
+        let options = [
+            PipelineOptions::default(),
+            PipelineOptions {
+                gaussian: GaussianEstimator::AngleDefect,
+                mean: MeanEstimator::Cotangent,
+                fit_weighting: geom::FitWeighting::Area,
+                solver: geom::FitSolver::Simd,
+                normals: NormalSource::Pca,
+                boundary: BoundaryHandling::OneSided,
+                ..Default::default()
+            },
+            PipelineOptions {
+                gaussian: GaussianEstimator::AngleDefect,
+                mean: MeanEstimator::Cotangent,
+                precision: geom::Precision::Double,
+                normals: NormalSource::Pca,
+                ..Default::default()
+            },
+        ];
+
+        // Com uma thread só, o rayon roda os laços em série, na ordem.
+        let pool = |threads| rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        let (serial_pool, parallel_pool) = (pool(1), pool(4));
+
+        for opts in &options {
+            let serial = serial_pool.install(|| VertexAttributes::compute_on(&mesh, opts));
+            let parallel = parallel_pool.install(|| VertexAttributes::compute_on(&mesh, opts));
+
+            assert_identical(&parallel, &serial);
+        }
+    }
+}
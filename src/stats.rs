@@ -0,0 +1,189 @@
+//! # Estatísticas
+//!
+//! Utilidades para resumir as grandezas calculadas em [`crate::geom`]
+//! (uma por vértice) em alguns poucos números, como mínimo, máximo,
+//! média e percentis.
+
+/// Resumo estatístico de uma grandeza definida em cada vértice.
+///
+/// Valores não finitos (`NaN` e infinitos, que aparecem em vértices
+/// degenerados) são ignorados.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Summary {
+    /// Quantidade de valores finitos considerados.
+    pub count: usize,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub median: f32,
+    /// Percentil 5.
+    pub p05: f32,
+    /// Percentil 25.
+    pub p25: f32,
+    /// Percentil 75.
+    pub p75: f32,
+    /// Percentil 95.
+    pub p95: f32,
+}
+
+impl Summary {
+    /// Calcula o resumo de `values`.
+    ///
+    /// Retorna `None` se não houver nenhum valor finito.
+    pub fn from_values(values: &[f32]) -> Option<Summary> {
+        let mut sorted = values
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect::<Vec<_>>();
+
+        if sorted.is_empty() {
+            return None;
+        }
+
+        sorted.sort_by(f32::total_cmp);
+
+        // Acumulamos em f64 para não perdermos precisão em malhas grandes.
+        let sum: f64 = sorted.iter().map(|&v| v as f64).sum();
+
+        Some(Summary {
+            count: sorted.len(),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            mean: (sum / sorted.len() as f64) as f32,
+            median: percentile(&sorted, 50.0),
+            p05: percentile(&sorted, 5.0),
+            p25: percentile(&sorted, 25.0),
+            p75: percentile(&sorted, 75.0),
+            p95: percentile(&sorted, 95.0),
+        })
+    }
+}
+
+/// Calcula o percentil `p` (entre 0 e 100) de uma lista já ordenada,
+/// interpolando linearmente entre os dois valores mais próximos.
+///
+/// `sorted` não pode ser vazia.
+pub fn percentile(sorted: &[f32], p: f32) -> f32 {
+    let rank = (p / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f32;
+
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    let t = rank - lo as f32;
+
+    sorted[lo] + t * (sorted[hi] - sorted[lo])
+}
+
+/// Calcula a curvatura total $\sum_i K_i A_i$, onde $A_i$ é a área
+/// associada ao vértice $i$.
+///
+/// Pelo teorema de Gauss-Bonnet, numa superfície fechada esse valor
+/// deve se aproximar de $2 \pi \chi$, onde $\chi$ é a característica
+/// de Euler da superfície. Vértices com curvatura não finita são ignorados.
+pub fn total_curvature(curvatures: &[f32], areas: &[f32]) -> f32 {
+    curvatures
+        .iter()
+        .zip(areas.iter())
+        .filter(|(k, _)| k.is_finite())
+        .map(|(&k, &a)| k as f64 * a as f64)
+        .sum::<f64>() as f32
+}
+
+/// Erro de uma estimativa em relação aos valores exatos.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ErrorNorms {
+    /// Quantidade de vértices considerados.
+    pub count: usize,
+    /// Norma L2 do erro, normalizada pela área: a raiz de
+    /// $\sum_i A_i e_i^2 / \sum_i A_i$.
+    pub l2: f32,
+    /// Norma L∞ do erro, o maior erro absoluto.
+    pub linf: f32,
+}
+
+/// Calcula as normas do erro de `estimate` em relação a `exact`, ponderando
+/// cada vértice pela sua área em `areas`.
+///
+/// Vértices em que a estimativa ou o valor exato não são finitos, ou em que
+/// `skip` é verdadeiro, são ignorados. Retorna `None` se não sobrar nenhum.
+pub fn error_norms(estimate: &[f32], exact: &[f32], areas: &[f32], skip: &[bool]) -> Option<ErrorNorms> {
+    let mut count = 0;
+    let mut sum_sq = 0.0f64;
+    let mut area = 0.0f64;
+    let mut linf = 0.0f32;
+
+    for i in 0..estimate.len() {
+        let e = estimate[i] - exact[i];
+
+        if skip[i] || !e.is_finite() {
+            continue;
+        }
+
+        count += 1;
+        sum_sq += areas[i] as f64 * (e as f64).powi(2);
+        area += areas[i] as f64;
+        linf = linf.max(e.abs());
+    }
+
+    if count == 0 {
+        return None;
+    }
+
+    Some(ErrorNorms {
+        count,
+        l2: (sum_sq / area).sqrt() as f32,
+        linf,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_interpolates() {
+        let sorted = [1.0, 2.0, 4.0, 8.0, 16.0];
+
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 50.0), 4.0);
+        assert_eq!(percentile(&sorted, 100.0), 16.0);
+
+        // O posto 12.5% de 4 é 0.5: no meio entre o primeiro e o segundo.
+        assert_eq!(percentile(&sorted, 12.5), 1.5);
+        assert_eq!(percentile(&sorted, 87.5), 12.0);
+
+        // Fora de [0, 100], ficamos nos extremos.
+        assert_eq!(percentile(&sorted, -10.0), 1.0);
+        assert_eq!(percentile(&sorted, 150.0), 16.0);
+
+        assert_eq!(percentile(&[3.0], 42.0), 3.0);
+    }
+
+    #[test]
+    fn summary_ignores_non_finite_values() {
+        let values = [4.0, f32::NAN, 0.0, f32::INFINITY, 2.0, 3.0, f32::NEG_INFINITY, 1.0];
+
+        assert_eq!(
+            Summary::from_values(&values),
+            Some(Summary {
+                count: 5,
+                min: 0.0,
+                max: 4.0,
+                mean: 2.0,
+                median: 2.0,
+                p05: 0.2,
+                p25: 1.0,
+                p75: 3.0,
+                p95: 3.8,
+            }),
+        );
+    }
+
+    #[test]
+    fn summary_of_no_values() {
+        assert_eq!(Summary::from_values(&[]), None);
+        assert_eq!(Summary::from_values(&[f32::NAN, f32::INFINITY]), None);
+    }
+}
@@ -0,0 +1,500 @@
+//! # Superfícies de teste
+//!
+//! Geradores de malhas que amostram superfícies conhecidas (esfera, toro,
+//! cilindro, elipsóide, parabolóide hiperbólico, catenóide, helicóide e
+//! superfícies de revolução em geral), junto dos valores *exatos* das
+//! curvaturas em cada vértice. Com eles dá para medir o erro dos
+//! estimadores de [`crate::geom`] e [`crate::discrete`] contra a verdade,
+//! e não só no olho.
+//!
+//! Cada gerador recebe as dimensões da superfície e uma resolução `n`, que
+//! controla quantas divisões a malha tem (ver a documentação de cada um).
+//! As malhas saem com as normais exatas em [`Mesh::normals`], e os
+//! triângulos orientados de acordo com elas.
+//!
+//! As superfícies fechadas com pólos (esfera, elipsóide e as de revolução
+//! que tocam o eixo) usam a grade de latitude e longitude: em volta de cada
+//! pólo há um leque de triângulos finos, que é justamente onde os
+//! estimadores costumam errar mais.
+//!
+//! ## Convenção de sinal
+//!
+//! As curvaturas seguem a mesma convenção do ajuste do parabolóide: uma
+//! curvatura é positiva quando a superfície se curva para longe da normal.
+//! Assim, a esfera de raio $r$ com as normais para fora tem
+//! $\kappa_1 = \kappa_2 = 1/r$, e $H = 1/r$.
+//!
+//! As fórmulas são avaliadas em `f64`, e só o resultado é convertido para
+//! `f32`.
+
+use std::f64::consts::PI;
+
+use ultraviolet::Vec3;
+
+use crate::mesh::Mesh;
+
+/// Malha que amostra uma superfície conhecida, com as curvaturas exatas de
+/// cada vértice.
+///
+/// Todos os vetores são indexados pelo índice do vértice na malha.
+#[derive(Clone, Debug)]
+pub struct AnalyticSurface {
+    pub mesh: Mesh,
+    /// Curvatura gaussiana K.
+    pub gaussian: Vec<f32>,
+    /// Curvatura média H.
+    pub mean: Vec<f32>,
+    /// Menor curvatura principal.
+    pub k1: Vec<f32>,
+    /// Maior curvatura principal.
+    pub k2: Vec<f32>,
+}
+
+/// Esfera de raio `radius` centrada na origem, com `n` divisões ao redor
+/// do eixo `z` e `n/2` do pólo sul ao pólo norte.
+pub fn sphere(radius: f32, n: usize) -> AnalyticSurface {
+    let r = radius as f64;
+
+    revolution(
+        |t| {
+            let t = t as f64;
+            ProfilePoint::from_f64(
+                [r * t.sin(), -r * t.cos()],
+                [r * t.cos(), r * t.sin()],
+                [-r * t.sin(), r * t.cos()],
+            )
+        },
+        0.0..=std::f32::consts::PI,
+        false,
+        n,
+        (n / 2).max(2),
+    )
+}
+
+/// Toro centrado na origem, em volta do eixo `z`, com raio maior `major` e
+/// raio menor `minor`.
+///
+/// São `n` divisões ao redor do eixo, e um número proporcional (pelo menos
+/// 3) em volta do tubo, para que os triângulos fiquem parecidos com
+/// triângulos retângulos isósceles.
+pub fn torus(major: f32, minor: f32, n: usize) -> AnalyticSurface {
+    let (big, a) = (major as f64, minor as f64);
+    let n_tube = ((n as f64 * a / big).round() as usize).max(3);
+
+    revolution(
+        |t| {
+            let t = t as f64;
+            ProfilePoint::from_f64(
+                [big + a * t.cos(), a * t.sin()],
+                [-a * t.sin(), a * t.cos()],
+                [-a * t.cos(), -a * t.sin()],
+            )
+        },
+        0.0..=std::f32::consts::TAU,
+        true,
+        n,
+        n_tube,
+    )
+}
+
+/// Cilindro aberto de raio `radius` e altura `height`, em volta do eixo `z`
+/// e centrado na origem.
+///
+/// São `n` divisões ao redor do eixo, e um número proporcional ao longo
+/// dele. A malha tem bordo nas duas pontas.
+pub fn cylinder(radius: f32, height: f32, n: usize) -> AnalyticSurface {
+    let r = radius as f64;
+    let n_along = ((n as f64 * height as f64 / (2.0 * PI * r)).round() as usize).max(1);
+
+    revolution(
+        |t| ProfilePoint::from_f64([r, t as f64], [0.0, 1.0], [0.0, 0.0]),
+        -height / 2.0..=height / 2.0,
+        false,
+        n,
+        n_along,
+    )
+}
+
+/// Catenóide de "cintura" `c` (o raio do círculo mais estreito), em volta
+/// do eixo `z`, cortado na altura `height` e centrado na origem.
+///
+/// É uma superfície mínima ($H = 0$). São `n` divisões ao redor do eixo, e
+/// um número proporcional ao longo dele. A malha tem bordo nas duas pontas.
+pub fn catenoid(c: f32, height: f32, n: usize) -> AnalyticSurface {
+    let c = c as f64;
+    let n_along = ((n as f64 * height as f64 / (2.0 * PI * c)).round() as usize).max(1);
+
+    revolution(
+        |t| {
+            let s = t as f64 / c;
+            ProfilePoint::from_f64(
+                [c * s.cosh(), t as f64],
+                [s.sinh(), 1.0],
+                [s.cosh() / c, 0.0],
+            )
+        },
+        -height / 2.0..=height / 2.0,
+        false,
+        n,
+        n_along,
+    )
+}
+
+/// Ponto da curva geratriz de uma superfície de revolução, com as duas
+/// primeiras derivadas em relação ao parâmetro.
+///
+/// A curva fica no semiplano $(r, z)$, $r \geq 0$, e gira em volta do
+/// eixo `z`. O parâmetro não precisa ser o comprimento de arco.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProfilePoint {
+    /// Distância ao eixo.
+    pub r: f32,
+    /// Altura.
+    pub z: f32,
+    pub dr: f32,
+    pub dz: f32,
+    pub d2r: f32,
+    pub d2z: f32,
+}
+
+impl ProfilePoint {
+    fn from_f64(p: [f64; 2], d: [f64; 2], d2: [f64; 2]) -> ProfilePoint {
+        ProfilePoint {
+            r: p[0] as f32,
+            z: p[1] as f32,
+            dr: d[0] as f32,
+            dz: d[1] as f32,
+            d2r: d2[0] as f32,
+            d2z: d2[1] as f32,
+        }
+    }
+}
+
+/// Superfície de revolução gerada pela curva `profile`, com o parâmetro
+/// em `range`, girando em volta do eixo `z`.
+///
+/// São `n_around` divisões ao redor do eixo e `n_along` ao longo da curva.
+/// Se `closed`, a curva é fechada (como a do toro), e o último ponto é
+/// identificado com o primeiro. Pontas da curva sobre o eixo ($r = 0$)
+/// viram um único vértice, como os pólos da esfera; as outras pontas viram
+/// bordo. Como a curva é avaliada em `f32`, $r$ raramente é exatamente zero
+/// no eixo (na esfera, $\sin(\pi)$ arredondado dá cerca de $-8.7 \cdot
+/// 10^{-8}$ vezes o raio); consideramos sobre o eixo os pontos com $|r|$
+/// menor que um milionésimo do tamanho da curva.
+///
+/// As normais apontam para o lado direito da curva, andando no sentido
+/// crescente do parâmetro. Para uma curva que sobe com $r > 0$, elas
+/// apontam para fora.
+pub fn revolution<F>(
+    profile: F,
+    range: std::ops::RangeInclusive<f32>,
+    closed: bool,
+    n_around: usize,
+    n_along: usize,
+) -> AnalyticSurface
+where
+    F: Fn(f32) -> ProfilePoint,
+{
+    let (t0, t1) = (*range.start() as f64, *range.end() as f64);
+    let nv = n_along.max(1);
+
+    // Tamanho da curva: a maior coordenada entre os pontos amostrados.
+    let size = (0..=nv)
+        .map(|j| profile((t0 + j as f64 / nv as f64 * (t1 - t0)) as f32))
+        .map(|p| p.r.abs().max(p.z.abs()) as f64)
+        .fold(0.0, f64::max);
+    let axis_tolerance = 1e-6 * size.max(f64::MIN_POSITIVE);
+
+    let on_axis = |t: f64| (profile(t as f32).r.abs() as f64) < axis_tolerance;
+
+    let grid = Grid {
+        nu: n_around.max(3),
+        nv,
+        wrap_u: true,
+        wrap_v: closed,
+        pole_start: !closed && on_axis(t0),
+        pole_end: !closed && on_axis(t1),
+    };
+
+    grid.sample(|u, v| {
+        let phi = 2.0 * PI * u;
+        let p = profile((t0 + v * (t1 - t0)) as f32);
+        let (r, z) = (p.r as f64, p.z as f64);
+        let (dr, dz) = (p.dr as f64, p.dz as f64);
+        let (d2r, d2z) = (p.d2r as f64, p.d2z as f64);
+        let speed = (dr * dr + dz * dz).sqrt();
+
+        // Curvatura do meridiano (a da curva geratriz) e do paralelo.
+        let k_meridian = (dr * d2z - d2r * dz) / speed.powi(3);
+        let k_parallel = if r.abs() < axis_tolerance {
+            // No eixo, a superfície é umbílica.
+            k_meridian
+        } else {
+            dz / (r * speed)
+        };
+
+        Sample {
+            position: [r * phi.cos(), r * phi.sin(), z],
+            normal: [dz * phi.cos() / speed, dz * phi.sin() / speed, -dr / speed],
+            k1: k_meridian,
+            k2: k_parallel,
+        }
+    })
+}
+
+/// Elipsóide $x^2/a^2 + y^2/b^2 + z^2/c^2 = 1$, com `n` divisões ao redor
+/// do eixo `z` e `n/2` de um pólo ao outro.
+pub fn ellipsoid(a: f32, b: f32, c: f32, n: usize) -> AnalyticSurface {
+    let (a, b, c) = (a as f64, b as f64, c as f64);
+
+    let grid = Grid {
+        nu: n.max(3),
+        nv: (n / 2).max(2),
+        wrap_u: true,
+        wrap_v: false,
+        pole_start: true,
+        pole_end: true,
+    };
+
+    grid.sample(|u, v| {
+        let phi = 2.0 * PI * u;
+        let theta = PI * (1.0 - v);
+        let [x, y, z] = [
+            a * theta.sin() * phi.cos(),
+            b * theta.sin() * phi.sin(),
+            c * theta.cos(),
+        ];
+
+        // O gradiente da equação implícita é normal à superfície.
+        let grad = [x / (a * a), y / (b * b), z / (c * c)];
+        let g2 = grad.iter().map(|g| g * g).sum::<f64>();
+        let abc2 = (a * b * c).powi(2);
+
+        let k = 1.0 / (abc2 * g2 * g2);
+        let h = (a * a + b * b + c * c - (x * x + y * y + z * z)) / (2.0 * abc2 * g2.powf(1.5));
+        let (k1, k2) = principal_from(k, h);
+
+        Sample {
+            position: [x, y, z],
+            normal: grad.map(|g| g / g2.sqrt()),
+            k1,
+            k2,
+        }
+    })
+}
+
+/// Parabolóide hiperbólico (a "sela") $z = a (x^2 - y^2)$, sobre o quadrado
+/// $[-s, s]^2$ com `s = size / 2`, numa grade de `n` por `n` quadrados.
+///
+/// As normais apontam para cima ($+z$). A malha tem bordo.
+pub fn hyperbolic_paraboloid(a: f32, size: f32, n: usize) -> AnalyticSurface {
+    let (a, s) = (a as f64, size as f64 / 2.0);
+
+    let grid = Grid {
+        nu: n.max(1),
+        nv: n.max(1),
+        wrap_u: false,
+        wrap_v: false,
+        pole_start: false,
+        pole_end: false,
+    };
+
+    grid.sample(|u, v| {
+        let (x, y) = (s * (2.0 * u - 1.0), s * (2.0 * v - 1.0));
+        let (fx, fy) = (2.0 * a * x, -2.0 * a * y);
+        let w2 = 1.0 + fx * fx + fy * fy;
+        let w = w2.sqrt();
+
+        // Para um gráfico z = f(x, y), K = det(Hess f) / W^4. A média troca
+        // de sinal em relação à fórmula usual, pela nossa convenção.
+        let k = -4.0 * a * a / (w2 * w2);
+        let h = 4.0 * a.powi(3) * (x * x - y * y) / (w2 * w);
+        let (k1, k2) = principal_from(k, h);
+
+        Sample {
+            position: [x, y, a * (x * x - y * y)],
+            normal: [-fx / w, -fy / w, 1.0 / w],
+            k1,
+            k2,
+        }
+    })
+}
+
+/// Helicóide $(v \cos u, v \sin u, c\,u)$, com $v \in [-R, R]$ onde
+/// `R = radius`, dando `turns` voltas em torno do eixo `z`.
+///
+/// É uma superfície mínima ($H = 0$). São `n` divisões na direção radial,
+/// e um número proporcional ao longo das voltas. A malha tem bordo.
+pub fn helicoid(c: f32, radius: f32, turns: f32, n: usize) -> AnalyticSurface {
+    let (c, big) = (c as f64, radius as f64);
+    let span = 2.0 * PI * turns as f64;
+
+    let grid = Grid {
+        nu: ((n as f64 * turns as f64 * PI).round() as usize).max(1),
+        nv: n.max(1),
+        wrap_u: false,
+        wrap_v: false,
+        pole_start: false,
+        pole_end: false,
+    };
+
+    grid.sample(|u, v| {
+        let theta = span * (u - 0.5);
+        let rho = big * (2.0 * v - 1.0);
+        let len = (c * c + rho * rho).sqrt();
+        let kappa = c / (len * len);
+
+        Sample {
+            position: [rho * theta.cos(), rho * theta.sin(), c * theta],
+            normal: [c * theta.sin() / len, -c * theta.cos() / len, rho / len],
+            k1: -kappa,
+            k2: kappa,
+        }
+    })
+}
+
+/// Curvaturas principais a partir de K e H.
+fn principal_from(k: f64, h: f64) -> (f64, f64) {
+    // Arredondamentos podem deixar H² - K um pouco negativo em pontos
+    // umbílicos.
+    let disc = (h * h - k).max(0.0).sqrt();
+
+    (h - disc, h + disc)
+}
+
+/// Amostra da superfície num ponto do domínio.
+struct Sample {
+    position: [f64; 3],
+    /// Normal unitária.
+    normal: [f64; 3],
+    /// As duas curvaturas principais, em qualquer ordem.
+    k1: f64,
+    k2: f64,
+}
+
+/// Grade regular no domínio $[0, 1]^2$ de uma parametrização.
+///
+/// `nu` e `nv` são os números de divisões em cada direção. Uma direção que
+/// dá a volta (`wrap_*`) não repete a última linha de vértices. As linhas
+/// `v = 0` e `v = 1` podem ser colapsadas num único vértice (`pole_*`),
+/// quando a parametrização é singular ali, como nos pólos da esfera.
+struct Grid {
+    nu: usize,
+    nv: usize,
+    wrap_u: bool,
+    wrap_v: bool,
+    pole_start: bool,
+    pole_end: bool,
+}
+
+impl Grid {
+    fn columns(&self) -> usize {
+        if self.wrap_u { self.nu } else { self.nu + 1 }
+    }
+
+    fn rows(&self) -> usize {
+        if self.wrap_v { self.nv } else { self.nv + 1 }
+    }
+
+    /// Índice do vértice na coluna `i` e linha `j`.
+    fn index(&self, i: usize, j: usize) -> u32 {
+        let (cols, rows) = (self.columns(), self.rows());
+        let (i, j) = (i % cols, j % rows);
+        let skipped = self.pole_start as usize * (cols - 1);
+
+        let idx = if self.pole_start && j == 0 {
+            0
+        } else if self.pole_end && j == rows - 1 {
+            cols * (rows - 1) - skipped
+        } else {
+            cols * j + i - skipped
+        };
+
+        idx as u32
+    }
+
+    /// Avalia `f` em cada vértice da grade e monta a malha.
+    fn sample<F: Fn(f64, f64) -> Sample>(&self, f: F) -> AnalyticSurface {
+        let (cols, rows) = (self.columns(), self.rows());
+
+        let mut samples = Vec::with_capacity(cols * rows);
+        for j in 0..rows {
+            let is_pole = (self.pole_start && j == 0) || (self.pole_end && j == rows - 1);
+            let row = if is_pole { 1 } else { cols };
+
+            for i in 0..row {
+                samples.push(f(i as f64 / self.nu as f64, j as f64 / self.nv as f64));
+            }
+        }
+
+        let to_vec3 = |p: [f64; 3]| Vec3::new(p[0] as f32, p[1] as f32, p[2] as f32);
+        let positions: Vec<_> = samples.iter().map(|s| to_vec3(s.position)).collect();
+        let normals: Vec<_> = samples.iter().map(|s| to_vec3(s.normal)).collect();
+
+        let mut triangles = Vec::with_capacity(2 * self.nu * self.nv);
+        for j in 0..self.nv {
+            for i in 0..self.nu {
+                let a = self.index(i, j);
+                let b = self.index(i + 1, j);
+                let c = self.index(i + 1, j + 1);
+                let d = self.index(i, j + 1);
+
+                for mut tri in [[a, b, c], [a, c, d]] {
+                    // Nos pólos, um dos dois triângulos de cada quadrado
+                    // degenera.
+                    if tri[0] == tri[1] || tri[1] == tri[2] || tri[2] == tri[0] {
+                        continue;
+                    }
+
+                    let [p0, p1, p2] = tri.map(|v| positions[v as usize]);
+                    let n = tri.iter().map(|&v| normals[v as usize]).fold(Vec3::zero(), |acc, n| acc + n);
+                    if (p1 - p0).cross(p2 - p0).dot(n) < 0.0 {
+                        tri.swap(1, 2);
+                    }
+
+                    triangles.push(tri);
+                }
+            }
+        }
+
+        let (k1, k2): (Vec<_>, Vec<_>) = samples
+            .iter()
+            .map(|s| (s.k1.min(s.k2), s.k1.max(s.k2)))
+            .unzip();
+
+        AnalyticSurface {
+            mesh: Mesh {
+                normals: Some(normals),
+                ..Mesh::new(positions, triangles)
+            },
+            gaussian: k1.iter().zip(&k2).map(|(a, b)| (a * b) as f32).collect(),
+            mean: k1.iter().zip(&k2).map(|(a, b)| ((a + b) / 2.0) as f32).collect(),
+            k1: k1.into_iter().map(|k| k as f32).collect(),
+            k2: k2.into_iter().map(|k| k as f32).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::halfedge::HalfEdgeMesh;
+
+    fn assert_closed_sphere(surface: &AnalyticSurface, what: &str) {
+        let mesh = HalfEdgeMesh::new(&surface.mesh).unwrap();
+
+        assert_eq!(mesh.euler_characteristic(), 2, "{}", what);
+        assert!(mesh.boundary_loops().is_empty(), "{}", what);
+    }
+
+    #[test]
+    fn poles_are_closed_at_any_scale() {
+        for radius in [0.1, 1.0, 20.0, 100.0] {
+            assert_closed_sphere(&sphere(radius, 16), &format!("sphere of radius {}", radius));
+            assert_closed_sphere(
+                &ellipsoid(radius, 1.5 * radius, 2.0 * radius, 16),
+                &format!("ellipsoid of scale {}", radius),
+            );
+        }
+    }
+}
@@ -9,12 +9,311 @@
 //! o estado da placa de vídeo para a renderização, carrega a malha
 //! a partir do arquivo `.obj`, chama as funções de cálculo do módulo
 //! [`crate::geom`], e faz a renderização.
+//!
+//! As grandezas são pintadas com um dos mapas de [`crate::colormap`], e uma
+//! legenda no canto direito da janela mostra a escala das cores. O
+//! intervalo de valores coberto pelo mapa é calculado a partir dos dados
+//! (ver [`RangeMode`]), e pode ser ajustado e travado pelo teclado.
+//!
+//! A cena é vista por uma [`ArcballCamera`], que começa enquadrando a caixa
+//! que contém a malha e é controlada pelo mouse.
+
+use std::{fmt, path::PathBuf, str::FromStr, time::Duration};
+
+use glow::HasContext;
+use ultraviolet::{Vec2, Vec3};
+
+use crate::{
+    camera::ArcballCamera,
+    colormap::Colormap,
+    gfx::{font, rect_vertices, OverlayBuffer, OverlayVertex, Shader, Texture1D, VertexBuffer, OVERLAY_WHITE},
+    mesh::Mesh,
+    pipeline::{PipelineOptions, VertexAttributes},
+    stats,
+};
+
+/// Qual grandeza é pintada sobre a malha.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quantity {
+    /// Curvatura gaussiana K.
+    Gaussian,
+    /// Curvatura média H.
+    Mean,
+    /// Menor curvatura principal k1.
+    MinPrincipal,
+    /// Maior curvatura principal k2.
+    MaxPrincipal,
+    /// Índice de forma (ver [`crate::geom::shape_index`]).
+    ShapeIndex,
+    /// Resíduo do ajuste do parabolóide (ver
+    /// [`crate::geom::compute_fit_residuals`]).
+    FitResidual,
+    /// Normal média, pintada como uma cor RGB.
+    Normal,
+}
+
+impl Quantity {
+    /// Todas as grandezas, na ordem em que aparecem na linha de comando.
+    ///
+    /// No visualizador, as teclas de 1 a 7 escolhem a grandeza nessa ordem.
+    pub const ALL: [Quantity; 7] = [
+        Quantity::Gaussian,
+        Quantity::Mean,
+        Quantity::MinPrincipal,
+        Quantity::MaxPrincipal,
+        Quantity::ShapeIndex,
+        Quantity::FitResidual,
+        Quantity::Normal,
+    ];
+
+    /// Nome usado na linha de comando.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quantity::Gaussian => "gaussian",
+            Quantity::Mean => "mean",
+            Quantity::MinPrincipal => "k1",
+            Quantity::MaxPrincipal => "k2",
+            Quantity::ShapeIndex => "shape-index",
+            Quantity::FitResidual => "residual",
+            Quantity::Normal => "normal",
+        }
+    }
+
+    /// Nome por extenso, mostrado no título da janela.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Quantity::Gaussian => "Gaussian curvature",
+            Quantity::Mean => "mean curvature",
+            Quantity::MinPrincipal => "minimum principal curvature",
+            Quantity::MaxPrincipal => "maximum principal curvature",
+            Quantity::ShapeIndex => "shape index",
+            Quantity::FitResidual => "fit residual",
+            Quantity::Normal => "average normal",
+        }
+    }
+
+    /// Unidade da grandeza, em termos da unidade `u` das posições da malha,
+    /// ou `None` se ela não tem unidade.
+    pub fn unit(&self) -> Option<&'static str> {
+        match self {
+            Quantity::Gaussian => Some("1/u²"),
+            Quantity::Mean | Quantity::MinPrincipal | Quantity::MaxPrincipal => Some("1/u"),
+            Quantity::FitResidual => Some("u"),
+            Quantity::ShapeIndex | Quantity::Normal => None,
+        }
+    }
+
+    /// A grandeza seguinte em [`Quantity::ALL`], voltando ao começo no fim.
+    pub fn next(self) -> Quantity {
+        let i = Quantity::ALL.iter().position(|&q| q == self).unwrap();
 
-use std::{f32::consts::PI, time::Duration};
+        Quantity::ALL[(i + 1) % Quantity::ALL.len()]
+    }
+
+    /// A grandeza anterior em [`Quantity::ALL`], voltando ao fim no começo.
+    pub fn prev(self) -> Quantity {
+        let i = Quantity::ALL.iter().position(|&q| q == self).unwrap();
+
+        Quantity::ALL[(i + Quantity::ALL.len() - 1) % Quantity::ALL.len()]
+    }
+
+    /// Os valores escalares dessa grandeza, ou `None` se ela não é um
+    /// escalar.
+    fn values(self, attrs: &VertexAttributes) -> Option<&[f32]> {
+        match self {
+            Quantity::Gaussian => Some(&attrs.gaussian),
+            Quantity::Mean => Some(&attrs.mean),
+            Quantity::MinPrincipal => Some(&attrs.k1),
+            Quantity::MaxPrincipal => Some(&attrs.k2),
+            Quantity::ShapeIndex => Some(&attrs.shape_index),
+            Quantity::FitResidual => Some(&attrs.fit_residual),
+            Quantity::Normal => None,
+        }
+    }
+
+    /// Posição do buffer dessa grandeza entre os escalares enviados para a
+    /// placa de vídeo, na ordem de [`Quantity::ALL`].
+    fn scalar_slot(self) -> Option<usize> {
+        Quantity::ALL
+            .iter()
+            .filter(|q| q.is_scalar())
+            .position(|&q| q == self)
+    }
+
+    fn is_scalar(self) -> bool {
+        self != Quantity::Normal
+    }
+}
 
-use ultraviolet::Vec3;
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Quantity::ALL
+            .iter()
+            .find(|q| q.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown quantity `{}`", s))
+    }
+}
+
+/// Como o intervalo de valores coberto pelo mapa de cores é calculado a
+/// partir dos valores da grandeza.
+///
+/// Valores não finitos (de vértices degenerados) são ignorados, e valores
+/// fora do intervalo ficam com a cor da ponta mais próxima.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangeMode {
+    /// Do menor ao maior valor.
+    MinMax,
+    /// De $-m$ a $m$, onde $m$ é o maior valor absoluto.
+    Symmetric,
+    /// Do percentil 2 ao percentil 98. Ao contrário dos outros, não é
+    /// dominado pelos poucos vértices com valores extremos que quase toda
+    /// malha tem (em bicos, vincos e vértices mal ajustados).
+    Percentile,
+}
+
+impl RangeMode {
+    /// Todos os modos, na ordem em que aparecem na linha de comando.
+    pub const ALL: [RangeMode; 3] = [
+        RangeMode::MinMax,
+        RangeMode::Symmetric,
+        RangeMode::Percentile,
+    ];
+
+    /// Nome usado na linha de comando.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RangeMode::MinMax => "min-max",
+            RangeMode::Symmetric => "symmetric",
+            RangeMode::Percentile => "percentile",
+        }
+    }
+
+    /// Descrição curta, mostrada na legenda.
+    fn label(&self) -> &'static str {
+        match self {
+            RangeMode::MinMax => "min-max",
+            RangeMode::Symmetric => "±max",
+            RangeMode::Percentile => "2-98%",
+        }
+    }
+
+    /// O modo seguinte em [`RangeMode::ALL`], voltando ao começo no fim.
+    pub fn next(self) -> RangeMode {
+        let i = RangeMode::ALL.iter().position(|&m| m == self).unwrap();
+
+        RangeMode::ALL[(i + 1) % RangeMode::ALL.len()]
+    }
+
+    /// O intervalo de `values`.
+    ///
+    /// Se não há valores finitos, o intervalo é $[-1, 1]$, e se todos os
+    /// valores são iguais, ele é aumentado em volta deles, para que o mapa
+    /// de cores não tenha largura zero.
+    pub fn range(&self, values: &[f32]) -> (f32, f32) {
+        let mut sorted = values
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect::<Vec<_>>();
+
+        if sorted.is_empty() {
+            return (-1.0, 1.0);
+        }
+
+        sorted.sort_by(f32::total_cmp);
+
+        let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+
+        let (lo, hi) = match self {
+            RangeMode::MinMax => (min, max),
+            RangeMode::Symmetric => {
+                let m = min.abs().max(max.abs());
+
+                (-m, m)
+            }
+            RangeMode::Percentile => (stats::percentile(&sorted, 2.0), stats::percentile(&sorted, 98.0)),
+        };
 
-use crate::gfx::{Shader, VertexBuffer};
+        if hi > lo {
+            (lo, hi)
+        } else {
+            let pad = if lo == 0.0 { 1.0 } else { 0.5 * lo.abs() };
+
+            (lo - pad, hi + pad)
+        }
+    }
+}
+
+impl fmt::Display for RangeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for RangeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RangeMode::ALL
+            .iter()
+            .find(|m| m.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown range mode `{}`", s))
+    }
+}
+
+/// Opções de configuração do [`Viewer`].
+#[derive(Clone, Debug)]
+pub struct ViewerOptions {
+    /// Caminho do arquivo `.obj` a ser carregado.
+    pub mesh_path: PathBuf,
+
+    /// Grandeza pintada inicialmente sobre a malha.
+    pub quantity: Quantity,
+
+    /// Mapa de cores inicial.
+    pub colormap: Colormap,
+
+    /// Como o intervalo coberto pelo mapa de cores é calculado.
+    pub range_mode: RangeMode,
+
+    /// Largura inicial da janela, em pixels.
+    pub width: u32,
+
+    /// Altura inicial da janela, em pixels.
+    pub height: u32,
+
+    /// Velocidade de rotação do modelo, em rad/s. A rotação pode ser
+    /// ligada e desligada com [`Viewer::set_spinning`].
+    pub rotation_speed: f32,
+
+    /// Opções dos cálculos das curvaturas.
+    pub pipeline: PipelineOptions,
+}
+
+impl Default for ViewerOptions {
+    fn default() -> Self {
+        ViewerOptions {
+            mesh_path: PathBuf::from("res/models/suzanne.obj"),
+            quantity: Quantity::Gaussian,
+            colormap: Colormap::Coolwarm,
+            range_mode: RangeMode::Percentile,
+            width: 1280,
+            height: 720,
+            rotation_speed: 0.3,
+            pipeline: PipelineOptions::default(),
+        }
+    }
+}
 
 /// A struct `Viewer` armazena o estado da aplicação.
 pub struct Viewer<'a> {
@@ -25,196 +324,504 @@ pub struct Viewer<'a> {
     /// para determinar a renderização
     shader: Shader,
 
-    /// Buffer de vértices, normais e curvaturas que é enviado
+    /// Buffer de vértices, normais e grandezas escalares que é enviado
     /// para a placa de vídeo
     vao: VertexBuffer,
 
-    /// Matriz que representa a transformação de espaço de objeto -> espaço de
+    /// Grandeza pintada sobre a malha
+    quantity: Quantity,
+
+    /// Mapa de cores usado
+    colormap: Colormap,
+
+    /// As tabelas de cores, uma para cada mapa de [`Colormap::ALL`]
+    colormap_textures: Vec<Texture1D>,
+
+    /// Valores da grandeza que vão no começo e no fim do mapa de cores
+    range: (f32, f32),
+
+    /// Como `range` é calculado
+    range_mode: RangeMode,
+
+    /// Se `range` fica fixo quando a grandeza ou o modo mudam
+    range_locked: bool,
+
+    /// Os intervalos de cada grandeza escalar, em cada modo de
+    /// [`RangeMode::ALL`], calculados uma vez ao carregar a malha
+    auto_ranges: Vec<[(f32, f32); 3]>,
+
+    /// Shader e triângulos da legenda
+    overlay_shader: Shader,
+    overlay: OverlayBuffer,
+
+    /// Tamanho da janela, em pixels
+    size: (u32, u32),
+
+    /// A câmera, que dá a transformação de espaço do mundo -> espaço de
     /// tela para a projeção
-    cam_matrix: ultraviolet::Mat4,
+    camera: ArcballCamera,
+
+    /// Centro e raio da esfera que contém a caixa da malha
+    center: Vec3,
+    radius: f32,
 
     /// Matriz que representa a transformação ortogonal do modelo
     model_matrix: ultraviolet::Mat4,
 
     /// Ângulo de rotação do modelo
     rot: f32,
+
+    /// Velocidade de rotação do modelo, em rad/s
+    rotation_speed: f32,
+
+    /// Se o modelo está girando sozinho
+    spinning: bool,
 }
 
 impl<'a> Viewer<'a> {
     /// Constrói um novo `Viewer`.
-    pub fn new(gl: &'a glow::Context) -> Viewer {
+    pub fn new(gl: &'a glow::Context, opts: &ViewerOptions) -> Viewer<'a> {
         // Carrega o shader
-        let shader = Shader::new(&gl,
+        let shader = Shader::new(gl,
             include_str!("../res/shaders/simple.frag.glsl"),
             include_str!("../res/shaders/simple.vert.glsl"),
         ).expect("failed to load shader");
+        let overlay_shader = Shader::new(gl,
+            include_str!("../res/shaders/overlay.frag.glsl"),
+            include_str!("../res/shaders/overlay.vert.glsl"),
+        ).expect("failed to load shader");
 
-        // Carrega o modelo.
-        let mut load_opts = tobj::GPU_LOAD_OPTIONS;
-        load_opts.single_index = false;
+        // Carrega as tabelas de cores.
+        let colormap_textures = Colormap::ALL
+            .iter()
+            .map(|c| Texture1D::from_rgb(gl, &c.lut(COLORMAP_SIZE)))
+            .collect();
 
-        let (models, _) = tobj::load_obj(
-            "res/models/suzanne.obj",
-            &load_opts,
-        ).expect("failed to load model");
+        // Carrega o modelo.
+        let models = crate::load_obj(&opts.mesh_path)
+            .unwrap_or_else(|e| panic!("failed to load model {}: {}", opts.mesh_path.display(), e));
 
         // Constrói o buffer.
         // Aqui que chamamos as computações.
-        let vao = {
+        let (vao, auto_ranges, (center, radius)) = {
             let model = &models[0];
-            let mesh = &model.mesh;
-
-            // Calcula as vizinhanças.
-            let nbhds = crate::geom::compute_neighborhoods(mesh);
-            // Calcula as normais médias.
-            let raw_avg_normals = crate::geom::compute_avg_normals(mesh);
-            // Calcula as bases dos planos tangentes.
-            let tangent_basii = crate::geom::compute_tangent_basis(mesh, &nbhds, &raw_avg_normals);
-            // Calcula as matrizes dos shape operators.
-            let shape_ops = crate::geom::compute_shape_operator(mesh, &nbhds, &tangent_basii);
-            // Calcula as curvaturas gaussianas e normais.
-            let raw_curvatures = crate::geom::compute_curvatures(&shape_ops);
+            let mesh = Mesh::try_from(&model.mesh)
+                .unwrap_or_else(|e| panic!("failed to process model {}: {}", opts.mesh_path.display(), e));
+
+            // Calcula as normais médias e as curvaturas.
+            let attrs = VertexAttributes::compute(&mesh, &opts.pipeline)
+                .unwrap_or_else(|e| panic!("failed to process model {}: {}", opts.mesh_path.display(), e));
+            let raw_avg_normals = &attrs.normals;
 
             let raw_positions = &mesh.positions;
-            let raw_normals = &mesh.normals;
+            let bounds = bounding_sphere(raw_positions);
 
             // Prepara os vértices de um modo que a placa de vídeo espera.
             let vertices = mesh
-                .indices
-                .chunks_exact(3)
-                .map(|idxs| {
-                    let v1 = Vec3::new(
-                        raw_positions[3 * idxs[0] as usize + 0],
-                        raw_positions[3 * idxs[0] as usize + 1],
-                        raw_positions[3 * idxs[0] as usize + 2],
-                    );
-                    let v2 = Vec3::new(
-                        raw_positions[3 * idxs[1] as usize + 0],
-                        raw_positions[3 * idxs[1] as usize + 1],
-                        raw_positions[3 * idxs[1] as usize + 2],
-                    );
-                    let v3 = Vec3::new(
-                        raw_positions[3 * idxs[2] as usize + 0],
-                        raw_positions[3 * idxs[2] as usize + 1],
-                        raw_positions[3 * idxs[2] as usize + 2],
-                    );
-
-                    [v1, v2, v3]
-                })
-                .flatten()
-                .collect::<Vec<_>>();
-
-            // Prepara as normais de um modo que a placa de vídeo espera.
-            let _normals = mesh
-                .normal_indices
-                .chunks_exact(3)
-                .map(|idxs| {
-                    let v1 = Vec3::new(
-                        raw_normals[3 * idxs[0] as usize + 0],
-                        raw_normals[3 * idxs[0] as usize + 1],
-                        raw_normals[3 * idxs[0] as usize + 2],
-                    );
-                    let v2 = Vec3::new(
-                        raw_normals[3 * idxs[1] as usize + 0],
-                        raw_normals[3 * idxs[1] as usize + 1],
-                        raw_normals[3 * idxs[1] as usize + 2],
-                    );
-                    let v3 = Vec3::new(
-                        raw_normals[3 * idxs[2] as usize + 0],
-                        raw_normals[3 * idxs[2] as usize + 1],
-                        raw_normals[3 * idxs[2] as usize + 2],
-                    );
-
-                    [v1, v2, v3]
-                })
-                .flatten()
+                .triangles
+                .iter()
+                .flat_map(|idxs| idxs.map(|idx| raw_positions[idx as usize]))
                 .collect::<Vec<_>>();
 
             // Prepara as normais médias de um modo que a placa de vídeo espera.
             let avg_normals = mesh
-                .indices
-                .chunks_exact(3)
-                .map(|idxs| {
-                    let n1 = raw_avg_normals[idxs[0] as usize];
-                    let n2 = raw_avg_normals[idxs[1] as usize];
-                    let n3 = raw_avg_normals[idxs[2] as usize];
-
-                    [n1, n2, n3]
-                })
-                .flatten()
+                .triangles
+                .iter()
+                .flat_map(|idxs| idxs.map(|idx| raw_avg_normals[idx as usize]))
                 .collect::<Vec<_>>();
 
-            // Prepara as curvaturas gaussianas de um modo que a placa de vídeo espera.
-            let curvatures = mesh
-                .indices
-                .chunks_exact(3)
-                .map(|idxs| {
-                    let k1 = raw_curvatures[idxs[0] as usize].0;
-                    let k2 = raw_curvatures[idxs[1] as usize].0;
-                    let k3 = raw_curvatures[idxs[2] as usize].0;
+            // Prepara as grandezas escalares de um modo que a placa de vídeo
+            // espera. Todas são enviadas de uma vez, para que a grandeza
+            // pintada possa ser trocada sem recarregar a malha. Vértices
+            // degenerados (com valores NaN) são pintados como se fossem
+            // planos.
+            let scalars = Quantity::ALL
+                .iter()
+                .filter_map(|q| q.values(&attrs))
+                .collect::<Vec<_>>();
+
+            let auto_ranges = scalars
+                .iter()
+                .map(|values| RangeMode::ALL.map(|mode| mode.range(values)))
+                .collect();
+
+            let scalars = scalars
+                .into_iter()
+                .map(|values| {
+                    let value = |idx: u32| {
+                        let k = values[idx as usize];
 
-                    [k1, k2, k3]
+                        if k.is_finite() { k } else { 0.0 }
+                    };
+
+                    mesh.triangles
+                        .iter()
+                        .flat_map(|idxs| idxs.map(value))
+                        .collect::<Vec<_>>()
                 })
-                .flatten()
-                .collect::<Vec<_>>();
+                .collect();
 
             // Constrói o buffer e copia os dados para a placa de vídeo.
-            VertexBuffer::from_mesh(gl, vertices, Some(avg_normals), Some(curvatures))
+            (VertexBuffer::from_mesh(gl, vertices, Some(avg_normals), scalars), auto_ranges, bounds)
         };
 
-        // Constrói a matriz da transformação da projeção.
-        let cam_matrix = {
-            let view = ultraviolet::Mat4::look_at(
-                // Observador está no ponto (2, 2, 2) ...
-                Vec3::new(2.0, 2.0, 2.0),
-                // ... olhando para o ponto (0, 0, 0) ...
-                Vec3::zero(),
-                // ... e o topo de sua cabeça aponta para (0, 1, 0).
-                Vec3::new(0.0, 1.0, 0.0),
-            );
-
-            let projection = ultraviolet::projection::perspective_gl(
-                // Campo de visão de PI / 3...
-                PI / 3.0,
-                // ... com aspecto de 16/9 (widescreen) ...
-                16.0/9.0,
-                // ... e considerando tudo numa distância entre 1.0 ...
-                1.0,
-                // ... e 100.0.
-                100.0,
-            );
-
-            projection * view
-        };
+        // Enquadra a malha.
+        let camera = ArcballCamera::new(center, radius, (opts.width, opts.height));
 
-        Viewer {
+        let mut viewer = Viewer {
             gl,
             shader,
             vao,
-            cam_matrix,
+            quantity: opts.quantity,
+            colormap: opts.colormap,
+            colormap_textures,
+            range: (-1.0, 1.0),
+            range_mode: opts.range_mode,
+            range_locked: false,
+            auto_ranges,
+            overlay_shader,
+            overlay: OverlayBuffer::new(gl),
+            size: (opts.width, opts.height),
+            camera,
+            center,
+            radius,
             model_matrix: ultraviolet::Mat4::identity(),
             rot: 0.0,
+            rotation_speed: opts.rotation_speed,
+            spinning: opts.rotation_speed != 0.0,
+        };
+
+        viewer.set_quantity(opts.quantity);
+
+        viewer
+    }
+
+    /// A grandeza pintada sobre a malha.
+    pub fn quantity(&self) -> Quantity {
+        self.quantity
+    }
+
+    /// Troca a grandeza pintada sobre a malha.
+    pub fn set_quantity(&mut self, quantity: Quantity) {
+        if let Some(slot) = quantity.scalar_slot() {
+            self.vao.select_scalar(self.gl, slot);
         }
+
+        self.quantity = quantity;
+        self.update_range();
     }
 
-    pub fn update(&mut self, delta: Duration) {
-        let speed = 0.3; // 0.1 rad/s
+    /// O mapa de cores usado.
+    pub fn colormap(&self) -> Colormap {
+        self.colormap
+    }
+
+    /// Troca o mapa de cores.
+    pub fn set_colormap(&mut self, colormap: Colormap) {
+        self.colormap = colormap;
+        self.update_legend();
+    }
+
+    /// Como o intervalo coberto pelo mapa de cores é calculado.
+    pub fn range_mode(&self) -> RangeMode {
+        self.range_mode
+    }
+
+    /// Troca o modo do intervalo coberto pelo mapa de cores. Se o intervalo
+    /// está travado, ele só muda quando for destravado.
+    pub fn set_range_mode(&mut self, mode: RangeMode) {
+        self.range_mode = mode;
+        self.update_range();
+    }
+
+    /// Se o intervalo coberto pelo mapa de cores está travado.
+    pub fn range_locked(&self) -> bool {
+        self.range_locked
+    }
+
+    /// Trava ou destrava o intervalo coberto pelo mapa de cores.
+    ///
+    /// Travado, ele continua o mesmo quando a grandeza ou o modo mudam, o
+    /// que serve para comparar grandezas na mesma escala. Ao destravar, ele
+    /// volta a ser calculado a partir dos dados.
+    pub fn set_range_locked(&mut self, locked: bool) {
+        self.range_locked = locked;
+        self.update_range();
+    }
+
+    /// Multiplica a largura do intervalo coberto pelo mapa de cores por
+    /// `factor`, mantendo o centro. O ajuste vale até a grandeza ou o modo
+    /// mudarem, ou até o intervalo ser destravado.
+    pub fn scale_range(&mut self, factor: f32) {
+        let (lo, hi) = self.range;
+        let center = 0.5 * (lo + hi);
+        let half = 0.5 * (hi - lo) * factor;
+
+        if half > 0.0 && half.is_finite() {
+            self.range = (center - half, center + half);
+            self.update_legend();
+        }
+    }
+
+    /// Recalcula o intervalo a partir dos dados, se ele não está travado.
+    fn update_range(&mut self) {
+        if !self.range_locked {
+            if let Some(slot) = self.quantity.scalar_slot() {
+                let mode = RangeMode::ALL.iter().position(|&m| m == self.range_mode).unwrap();
+
+                self.range = self.auto_ranges[slot][mode];
+            }
+        }
+
+        self.update_legend();
+    }
+
+    /// Os valores que vão no começo e no fim do mapa de cores.
+    ///
+    /// Mapas divergentes têm o zero no meio, então o intervalo é aumentado
+    /// para ficar simétrico.
+    fn color_range(&self) -> (f32, f32) {
+        let (lo, hi) = self.range;
+
+        if self.colormap.is_diverging() {
+            let m = lo.abs().max(hi.abs());
+
+            (-m, m)
+        } else {
+            (lo, hi)
+        }
+    }
+
+    /// Refaz os triângulos da legenda.
+    fn update_legend(&mut self) {
+        let vertices = if self.quantity == Quantity::Normal {
+            Vec::new()
+        } else {
+            let footer = if self.range_locked {
+                format!("{}, locked", self.range_mode.label())
+            } else {
+                self.range_mode.label().to_string()
+            };
+
+            legend_vertices(self.quantity, self.color_range(), &footer, self.size)
+        };
+
+        self.overlay.set(self.gl, &vertices);
+    }
+
+    /// Gira a câmera pelo movimento do mouse de `from` para `to`, em
+    /// pixels da área de desenho (os mesmos de [`Viewer::resize`]).
+    pub fn rotate_camera(&mut self, from: (i32, i32), to: (i32, i32)) {
+        self.camera.rotate(from, to);
+    }
+
+    /// Aproxima (`steps` positivo) ou afasta a câmera.
+    pub fn zoom_camera(&mut self, steps: f32) {
+        self.camera.zoom(steps);
+    }
+
+    /// Arrasta a cena por `(dx, dy)` pixels da área de desenho.
+    pub fn pan_camera(&mut self, dx: i32, dy: i32) {
+        self.camera.pan(dx, dy);
+    }
+
+    /// Ajusta a renderização a um novo tamanho da janela, em pixels: a
+    /// área de desenho do OpenGL, o aspecto da projeção e a posição da
+    /// legenda.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        // Janelas minimizadas podem ter tamanho zero.
+        let size = (width.max(1), height.max(1));
+
+        if size == self.size {
+            return;
+        }
+
+        unsafe { self.gl.viewport(0, 0, size.0 as i32, size.1 as i32) };
+
+        self.size = size;
+        self.camera.set_size(size);
+        self.update_legend();
+    }
+
+    /// Volta a câmera para o enquadramento inicial da malha.
+    pub fn frame_camera(&mut self) {
+        self.camera.frame(self.center, self.radius);
+    }
 
-        self.rot += speed * delta.as_secs_f32();
+    /// Se o modelo está girando sozinho.
+    pub fn spinning(&self) -> bool {
+        self.spinning
+    }
+
+    /// Liga ou desliga a rotação automática do modelo.
+    pub fn set_spinning(&mut self, spinning: bool) {
+        self.spinning = spinning;
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        if self.spinning {
+            self.rot += self.rotation_speed * delta.as_secs_f32();
+        }
 
-        self.model_matrix = ultraviolet::Mat4::from_euler_angles(
-            0.0,
-            self.rot,
-            0.0,
-        );
+        // Gira em torno do eixo vertical que passa pelo centro da malha, e
+        // não pela origem, para que modelos fora do centro não saiam da
+        // tela.
+        self.model_matrix = ultraviolet::Mat4::from_translation(self.center)
+            * ultraviolet::Mat4::from_rotation_y(self.rot)
+            * ultraviolet::Mat4::from_translation(-self.center);
     }
 
     /// Renderiza a cena.
     pub fn render(&self) {
+        let (lo, hi) = self.color_range();
+
+        let colormap = Colormap::ALL.iter().position(|&c| c == self.colormap).unwrap();
+        self.colormap_textures[colormap].bind(self.gl, 0);
+
         self.shader.bind(self.gl);
-        self.shader.uniform(self.gl, "_camera_mtx", &self.cam_matrix);
+        self.shader.uniform(self.gl, "_camera_mtx", &self.camera.matrix());
         self.shader.uniform(self.gl, "_model_mtx", &self.model_matrix);
+        self.shader.uniform(self.gl, "_show_normals", &((self.quantity == Quantity::Normal) as i32));
+        self.shader.uniform(self.gl, "_colormap", &0);
+        self.shader.uniform(self.gl, "_range", &Vec2::new(lo, hi));
 
         self.vao.draw(self.gl);
+
+        // A legenda fica por cima de tudo.
+        unsafe { self.gl.disable(glow::DEPTH_TEST) };
+
+        self.overlay_shader.bind(self.gl);
+        self.overlay_shader.uniform(self.gl, "_screen_size", &Vec2::new(self.size.0 as f32, self.size.1 as f32));
+        self.overlay_shader.uniform(self.gl, "_colormap", &0);
+
+        self.overlay.draw(self.gl);
+
+        unsafe { self.gl.enable(glow::DEPTH_TEST) };
+    }
+}
+
+/// Centro e raio da menor esfera que contém a caixa alinhada aos eixos
+/// que contém os pontos.
+fn bounding_sphere(points: &[Vec3]) -> (Vec3, f32) {
+    let mut points = points.iter().filter(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite());
+
+    let Some(&first) = points.next() else {
+        return (Vec3::zero(), 1.0);
+    };
+
+    let (min, max) = points.fold((first, first), |(min, max), &p| (min.min_by_component(p), max.max_by_component(p)));
+
+    (0.5 * (min + max), 0.5 * (max - min).mag())
+}
+
+/// Número de entradas das tabelas de cores.
+const COLORMAP_SIZE: usize = 256;
+
+/// Tamanho de cada pixel da fonte da legenda, em pixels da tela.
+const LEGEND_FONT_SCALE: f32 = 2.0;
+
+/// Monta os triângulos da legenda: uma barra vertical com o mapa de cores
+/// no canto direito da janela, os valores do começo, do meio e do fim do
+/// intervalo ao lado dela, o nome e a unidade da grandeza em cima, e
+/// `footer` embaixo.
+fn legend_vertices(
+    quantity: Quantity,
+    (lo, hi): (f32, f32),
+    footer: &str,
+    (width, height): (u32, u32),
+) -> Vec<OverlayVertex> {
+    let (width, height) = (width as f32, height as f32);
+    let scale = LEGEND_FONT_SCALE;
+    let text_height = font::GLYPH_HEIGHT as f32 * scale;
+
+    let margin = 24.0;
+    let bar_width = 24.0;
+    let bar_x1 = width - margin;
+    let bar_x0 = bar_x1 - bar_width;
+    let bar_y0 = 2.0 * margin + text_height;
+    let bar_y1 = height - 2.0 * margin;
+
+    let mut vertices = Vec::new();
+
+    // Se a janela é pequena demais, não há onde pôr a legenda.
+    if bar_y1 - bar_y0 < 4.0 * text_height || bar_x0 < margin {
+        return vertices;
+    }
+
+    // Texto alinhado à direita em `right`.
+    let text = |vertices: &mut Vec<OverlayVertex>, s: &str, right: f32, top: f32| {
+        let left = right - font::text_width(s, scale);
+
+        for rect in font::text_rects(s, left, top, scale) {
+            vertices.extend(rect_vertices(rect, OVERLAY_WHITE, OVERLAY_WHITE));
+        }
+    };
+
+    let title = match quantity.unit() {
+        Some(unit) => format!("{} ({})", quantity.label(), unit),
+        None => quantity.label().to_string(),
+    };
+    text(&mut vertices, &title, bar_x1, margin);
+    text(&mut vertices, footer, bar_x1, bar_y1 + 12.0);
+
+    // Os valores, com um traço apontando para a barra.
+    for (t, y) in [(1.0, bar_y0), (0.5, 0.5 * (bar_y0 + bar_y1)), (0.0, bar_y1)] {
+        let value = lo + t * (hi - lo);
+
+        text(&mut vertices, &format_value(value), bar_x0 - 12.0, y - 0.5 * text_height);
+        vertices.extend(rect_vertices([bar_x0 - 8.0, y - 1.0, bar_x0, y + 1.0], OVERLAY_WHITE, OVERLAY_WHITE));
+    }
+
+    // A barra, com uma borda branca. O topo é o fim do mapa, e a placa de
+    // vídeo interpola as cores entre o topo e a base.
+    vertices.extend(rect_vertices([bar_x0 - 1.0, bar_y0 - 1.0, bar_x1 + 1.0, bar_y1 + 1.0], OVERLAY_WHITE, OVERLAY_WHITE));
+    vertices.extend(rect_vertices([bar_x0, bar_y0, bar_x1, bar_y1], 1.0, 0.0));
+
+    vertices
+}
+
+/// Escreve um valor da legenda: com casas decimais fixas se ele não é nem
+/// muito grande nem muito pequeno, e em notação científica se é.
+fn format_value(value: f32) -> String {
+    let abs = value.abs();
+
+    if abs == 0.0 {
+        "0".to_string()
+    } else if (1e-2..1e4).contains(&abs) {
+        format!("{:.3}", value)
+    } else {
+        format!("{:.2e}", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_modes() {
+        // 0, 1, ..., 100, com um valor fora da curva e alguns NaN.
+        let mut values = (0..=100).map(|i| i as f32).collect::<Vec<_>>();
+        values.extend([f32::NAN, -1000.0, f32::INFINITY]);
+
+        assert_eq!(RangeMode::MinMax.range(&values), (-1000.0, 100.0));
+        assert_eq!(RangeMode::Symmetric.range(&values), (-1000.0, 1000.0));
+
+        // São 102 valores finitos, contando o -1000, e os percentis 2 e 98
+        // estão nas posições 2.02 e 98.98: o valor fora da curva não
+        // estica o intervalo.
+        assert_eq!(RangeMode::Percentile.range(&values), (1.02, 97.98));
+    }
+
+    #[test]
+    fn degenerate_ranges_are_widened() {
+        for mode in RangeMode::ALL {
+            assert_eq!(mode.range(&[]), (-1.0, 1.0));
+            assert_eq!(mode.range(&[f32::NAN]), (-1.0, 1.0));
+            assert_eq!(mode.range(&[0.0; 4]), (-1.0, 1.0));
+        }
+
+        assert_eq!(RangeMode::MinMax.range(&[-2.0; 4]), (-3.0, -1.0));
+        assert_eq!(RangeMode::Percentile.range(&[2.0; 4]), (1.0, 3.0));
     }
 }
@@ -0,0 +1,197 @@
+//! # Janela
+//!
+//! Cria a janela SDL e o contexto OpenGL, e roda o loop principal do
+//! [`Viewer`].
+
+use std::time::Instant;
+
+use glow::HasContext;
+use gauss::viewer::{Quantity, Viewer, ViewerOptions};
+use sdl2::{
+    event::{Event, WindowEvent},
+    keyboard::{Keycode, Mod},
+    mouse::MouseWheelDirection,
+    video::FullscreenType,
+};
+
+/// Abre a janela e roda o loop principal do visualizador.
+///
+/// Mouse:
+///
+/// - botão esquerdo: gira a câmera;
+/// - botão do meio (ou Shift e o botão esquerdo): arrasta a cena;
+/// - roda: aproxima e afasta a câmera.
+///
+/// Teclas:
+///
+/// - 1 a 7: escolhem a grandeza pintada, na ordem de [`Quantity::ALL`];
+/// - Tab e Shift+Tab: passam para a grandeza seguinte ou anterior;
+/// - C: passa para o mapa de cores seguinte;
+/// - R: passa para o modo seguinte do intervalo das cores;
+/// - L: trava ou destrava o intervalo das cores;
+/// - [ e ]: estreitam ou alargam o intervalo das cores;
+/// - Espaço: liga ou desliga a rotação automática do modelo;
+/// - F: volta a câmera para o enquadramento inicial;
+/// - F11: entra e sai da tela cheia.
+///
+/// Segurar C, R, L, Espaço, F ou F11 não repete a ação, para que as teclas
+/// que ligam e desligam alguma coisa não fiquem piscando.
+///
+/// A janela pode ser redimensionada; a imagem acompanha o novo tamanho.
+pub fn run(opts: &ViewerOptions) {
+    let sdl = sdl2::init().unwrap();
+    let video = sdl.video().unwrap();
+    let gl_attr = video.gl_attr();
+    gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
+    gl_attr.set_context_major_version(4);
+
+    let mut win = video
+        .window("Gauss", opts.width, opts.height)
+        .opengl()
+        .resizable()
+        .build()
+        .unwrap();
+
+    let _gl_ctx = win.gl_create_context().unwrap();
+    let gl = unsafe { glow::Context::from_loader_function(|s| video.gl_get_proc_address(s) as *const _) };
+
+    unsafe {
+        gl.enable(glow::DEPTH_TEST);
+    }
+
+    let mut viewer = Viewer::new(&gl, opts);
+    let (width, height) = win.drawable_size();
+    viewer.resize(width, height);
+    set_title(&mut win, viewer.quantity());
+
+    let mut last_frame = Instant::now();
+
+    let mut evt_loop = sdl.event_pump().unwrap();
+
+    'main: loop {
+        for evt in evt_loop.poll_iter() {
+            match evt {
+                Event::Quit { .. } => break 'main,
+                // Vem depois de qualquer mudança de tamanho, seja pelo
+                // usuário (junto com `Resized`) ou pela tela cheia.
+                Event::Window { win_event: WindowEvent::SizeChanged(..), .. } => {
+                    let (width, height) = win.drawable_size();
+                    viewer.resize(width, height);
+                }
+                Event::KeyDown { keycode: Some(Keycode::F11), repeat: false, .. } => {
+                    let fullscreen = match win.fullscreen_state() {
+                        FullscreenType::Off => FullscreenType::Desktop,
+                        _ => FullscreenType::Off,
+                    };
+
+                    if let Err(e) = win.set_fullscreen(fullscreen) {
+                        eprintln!("failed to toggle fullscreen: {}", e);
+                    }
+                }
+                Event::MouseMotion { mousestate, x, y, xrel, yrel, .. } => {
+                    let shift = sdl.keyboard().mod_state().intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+
+                    // A câmera trabalha nos pixels da área de desenho.
+                    let from = to_drawable(&win, (x - xrel, y - yrel));
+                    let to = to_drawable(&win, (x, y));
+
+                    if mousestate.middle() || (mousestate.left() && shift) {
+                        viewer.pan_camera(to.0 - from.0, to.1 - from.1);
+                    } else if mousestate.left() {
+                        viewer.rotate_camera(from, to);
+                    }
+                }
+                Event::MouseWheel { y, direction, .. } => {
+                    let steps = if direction == MouseWheelDirection::Flipped { -y } else { y };
+
+                    viewer.zoom_camera(steps as f32);
+                }
+                Event::KeyDown { keycode: Some(Keycode::Space), repeat: false, .. } => {
+                    viewer.set_spinning(!viewer.spinning());
+                }
+                Event::KeyDown { keycode: Some(Keycode::F), repeat: false, .. } => {
+                    viewer.frame_camera();
+                }
+                Event::KeyDown { keycode: Some(Keycode::C), repeat: false, .. } => {
+                    viewer.set_colormap(viewer.colormap().next());
+                }
+                Event::KeyDown { keycode: Some(Keycode::R), repeat: false, .. } => {
+                    viewer.set_range_mode(viewer.range_mode().next());
+                }
+                Event::KeyDown { keycode: Some(Keycode::L), repeat: false, .. } => {
+                    viewer.set_range_locked(!viewer.range_locked());
+                }
+                Event::KeyDown { keycode: Some(Keycode::LeftBracket), .. } => {
+                    viewer.scale_range(0.8);
+                }
+                Event::KeyDown { keycode: Some(Keycode::RightBracket), .. } => {
+                    viewer.scale_range(1.25);
+                }
+                Event::KeyDown { keycode: Some(key), keymod, .. } => {
+                    let current = viewer.quantity();
+
+                    let quantity = match key {
+                        Keycode::Tab if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) => current.prev(),
+                        Keycode::Tab => current.next(),
+                        _ => quantity_key(key).unwrap_or(current),
+                    };
+
+                    if quantity != current {
+                        viewer.set_quantity(quantity);
+                        set_title(&mut win, quantity);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let now = Instant::now();
+
+        let delta = now - last_frame;
+
+        viewer.update(delta);
+
+        unsafe {
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+
+            viewer.render();
+        }
+
+        win.gl_swap_window();
+
+        last_frame = now;
+    }
+}
+
+/// A grandeza escolhida pela tecla numérica `key`: 1 escolhe a primeira de
+/// [`Quantity::ALL`], e assim por diante. Há uma tecla para cada grandeza.
+fn quantity_key(key: Keycode) -> Option<Quantity> {
+    const KEYS: [Keycode; Quantity::ALL.len()] = [
+        Keycode::Num1, Keycode::Num2, Keycode::Num3,
+        Keycode::Num4, Keycode::Num5, Keycode::Num6,
+        Keycode::Num7,
+    ];
+
+    KEYS.iter().position(|&k| k == key).map(|i| Quantity::ALL[i])
+}
+
+/// Converte uma posição do mouse, nas coordenadas da janela, para pixels
+/// da área de desenho (`drawable_size`), que são os usados pela câmera.
+///
+/// Em telas de alta densidade os dois tamanhos são diferentes: a área de
+/// desenho pode ter, por exemplo, o dobro de pixels da janela em cada
+/// direção.
+fn to_drawable(win: &sdl2::video::Window, (x, y): (i32, i32)) -> (i32, i32) {
+    let (width, height) = win.size();
+    let (drawable_width, drawable_height) = win.drawable_size();
+
+    let scale = |v: i32, from: u32, to: u32| (v as f32 * to as f32 / from.max(1) as f32).round() as i32;
+
+    (scale(x, width, drawable_width), scale(y, height, drawable_height))
+}
+
+/// Mostra a grandeza pintada no título da janela.
+fn set_title(win: &mut sdl2::video::Window, quantity: Quantity) {
+    // Só falha se o título tiver um byte nulo.
+    let _ = win.set_title(&format!("Gauss: {}", quantity.label()));
+}